@@ -0,0 +1,74 @@
+//! Compares the quicksort variants and pdqsort on random data. Run with `cargo bench --bench quicksort`.
+//!
+//! Prints the median time of several runs for every sort and its speed-up over `quicksort`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use all_the_algorithms::sorting::{
+    block_quicksort, dual_pivot_quicksort, introsort, pdqsort, quicksort,
+};
+use rand::distributions::{Distribution, Standard};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+const LENGTHS: [usize; 3] = [1_000, 100_000, 1_000_000];
+
+const RUNS: usize = 7;
+
+type Sort<T> = (&'static str, fn(&mut [T]));
+
+fn sorts<T: Ord>() -> [Sort<T>; 6] {
+    [
+        ("quicksort", quicksort::<T>),
+        ("introsort", introsort::<T>),
+        ("dual_pivot_quicksort", dual_pivot_quicksort::<T>),
+        ("block_quicksort", block_quicksort::<T>),
+        ("pdqsort", pdqsort::<T>),
+        ("std sort_unstable", <[T]>::sort_unstable),
+    ]
+}
+
+/// Returns the median time `sort` takes for sorting copies of `input`
+fn measure<T: Clone>(input: &[T], sort: fn(&mut [T])) -> Duration {
+    let mut times: Vec<Duration> = (0..RUNS)
+        .map(|_| {
+            let mut items = input.to_vec();
+            let start = Instant::now();
+            sort(black_box(&mut items));
+            let time = start.elapsed();
+            black_box(items);
+            time
+        })
+        .collect();
+    times.sort();
+    times[RUNS / 2]
+}
+
+fn bench<T: Clone + Ord>(type_name: &str)
+where
+    Standard: Distribution<T>,
+{
+    let mut generator = StdRng::seed_from_u64(0x5eed);
+    for len in LENGTHS {
+        let input: Vec<T> = (0..len).map(|_| generator.gen()).collect();
+        println!("random {} x {}", type_name, len);
+
+        let mut baseline = None;
+        for (name, sort) in sorts::<T>() {
+            let time = measure(&input, sort);
+            let baseline = *baseline.get_or_insert(time);
+            println!(
+                "  {:<22} {:>10.3} ms  {:>5.2}x",
+                name,
+                time.as_secs_f64() * 1000.0,
+                baseline.as_secs_f64() / time.as_secs_f64()
+            );
+        }
+    }
+}
+
+fn main() {
+    bench::<i32>("i32");
+    bench::<u64>("u64");
+}
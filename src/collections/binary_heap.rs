@@ -0,0 +1,367 @@
+use std::cmp::Ordering;
+
+/// Identifies an element pushed to a `BinaryHeap`, so it can be updated or removed later.
+/// Once the element was removed the handle stays invalid, even if a new element reuses its slot.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub struct HeapHandle {
+    slot: usize,
+    generation: u64,
+}
+
+/// Where the element of a handle is stored. The generation is increased whenever the element is removed,
+/// so handles to earlier elements of the slot do not match anymore.
+#[derive(Clone, Copy)]
+struct Slot {
+    position: usize,
+    generation: u64,
+}
+
+/// A binary heap that keeps the greatest element according to its comparator at the top.
+/// Every pushed element gets a handle that can be used to change its value, which covers decrease-key
+/// on a min heap, or to remove it. The slots of removed elements are reused, so the memory only grows
+/// with the number of elements in the heap at once.
+pub struct BinaryHeap<T, C: FnMut(&T, &T) -> Ordering> {
+    /// the heap ordered elements together with the slot of their handle
+    entries: Vec<(T, usize)>,
+    /// the index in entries of the element of every slot
+    slots: Vec<Slot>,
+    /// the slots whose element was removed, they are given to the next pushed elements
+    free_slots: Vec<usize>,
+    compare: C,
+}
+
+impl<T: Ord> BinaryHeap<T, fn(&T, &T) -> Ordering> {
+    /// Creates a heap that returns the greatest element first
+    pub fn new_max() -> Self {
+        BinaryHeap::with_comparator(T::cmp)
+    }
+
+    /// Creates a heap that returns the smallest element first
+    pub fn new_min() -> Self {
+        BinaryHeap::with_comparator(|a: &T, b: &T| b.cmp(a))
+    }
+}
+
+impl<T, C: FnMut(&T, &T) -> Ordering> BinaryHeap<T, C> {
+    /// Creates a heap that returns the greatest element according to `compare` first
+    pub fn with_comparator(compare: C) -> Self {
+        BinaryHeap {
+            entries: Vec::new(),
+            slots: Vec::new(),
+            free_slots: Vec::new(),
+            compare,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the greatest element without removing it
+    pub fn peek(&self) -> Option<&T> {
+        self.entries.first().map(|entry| &entry.0)
+    }
+
+    /// Returns the element belonging to `handle`, if it is still in the heap
+    pub fn get(&self, handle: HeapHandle) -> Option<&T> {
+        let position = self.position(handle)?;
+        Some(&self.entries[position].0)
+    }
+
+    /// Adds an element and returns the handle to it. Takes O(log n).
+    pub fn push(&mut self, value: T) -> HeapHandle {
+        let position = self.entries.len();
+        let slot = match self.free_slots.pop() {
+            Some(slot) => {
+                self.slots[slot].position = position;
+                slot
+            }
+            None => {
+                self.slots.push(Slot {
+                    position,
+                    generation: 0,
+                });
+                self.slots.len() - 1
+            }
+        };
+        self.entries.push((value, slot));
+        self.sift_up(position);
+        HeapHandle {
+            slot,
+            generation: self.slots[slot].generation,
+        }
+    }
+
+    /// Removes and returns the greatest element. Takes O(log n).
+    pub fn pop(&mut self) -> Option<T> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        Some(self.remove_at(0))
+    }
+
+    /// Replaces the value of the element belonging to `handle` and restores the heap order.
+    /// Returns the old value, or gives `value` back if the element is not in the heap anymore.
+    pub fn update(&mut self, handle: HeapHandle, value: T) -> Result<T, T> {
+        let position = match self.position(handle) {
+            Some(position) => position,
+            None => return Err(value),
+        };
+        let old = std::mem::replace(&mut self.entries[position].0, value);
+        let position = self.sift_up(position);
+        self.sift_down(position);
+        Ok(old)
+    }
+
+    /// Removes the element belonging to `handle` and returns it, if it is still in the heap
+    pub fn remove(&mut self, handle: HeapHandle) -> Option<T> {
+        let position = self.position(handle)?;
+        Some(self.remove_at(position))
+    }
+
+    /// Removes all elements and returns them, greatest first
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        let mut result = Vec::with_capacity(self.len());
+        while let Some(value) = self.pop() {
+            result.push(value);
+        }
+        result
+    }
+
+    /// Returns the index in entries of the element belonging to `handle`, if it is still in the heap
+    fn position(&self, handle: HeapHandle) -> Option<usize> {
+        let slot = self.slots.get(handle.slot)?;
+        (slot.generation == handle.generation).then_some(slot.position)
+    }
+
+    fn remove_at(&mut self, position: usize) -> T {
+        let last = self.entries.len() - 1;
+        swap_entries(&mut self.entries, &mut self.slots, position, last);
+        let (value, slot) = self.entries.pop().unwrap();
+        self.slots[slot].generation += 1;
+        self.free_slots.push(slot);
+        if position < self.entries.len() {
+            let position = self.sift_up(position);
+            self.sift_down(position);
+        }
+        value
+    }
+
+    fn sift_up(&mut self, node: usize) -> usize {
+        let BinaryHeap {
+            entries,
+            slots,
+            compare,
+            ..
+        } = self;
+        sift_up(
+            entries,
+            node,
+            &mut |a: &(T, usize), b: &(T, usize)| compare(&a.0, &b.0) == Ordering::Less,
+            &mut |entries: &mut [(T, usize)], a, b| swap_entries(entries, slots, a, b),
+        )
+    }
+
+    fn sift_down(&mut self, node: usize) -> usize {
+        let BinaryHeap {
+            entries,
+            slots,
+            compare,
+            ..
+        } = self;
+        sift_down(
+            entries,
+            node,
+            &mut |a: &(T, usize), b: &(T, usize)| compare(&a.0, &b.0) == Ordering::Less,
+            &mut |entries: &mut [(T, usize)], a, b| swap_entries(entries, slots, a, b),
+        )
+    }
+}
+
+fn swap_entries<T>(entries: &mut [(T, usize)], slots: &mut [Slot], a: usize, b: usize) {
+    entries.swap(a, b);
+    slots[entries[a].1].position = a;
+    slots[entries[b].1].position = b;
+}
+
+/// Moves the element at `node` up while it is greater than its parent and returns where it ends up.
+/// `swap` has to exchange the two given elements and is called for every move.
+pub(crate) fn sift_up<T, F, S>(
+    elements: &mut [T],
+    mut node: usize,
+    is_less: &mut F,
+    swap: &mut S,
+) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+    S: FnMut(&mut [T], usize, usize),
+{
+    while node > 0 {
+        let parent = (node - 1) / 2;
+        if !is_less(&elements[parent], &elements[node]) {
+            break;
+        }
+        swap(elements, parent, node);
+        node = parent;
+    }
+    node
+}
+
+/// Moves the element at `node` down while one of its children is greater and returns where it ends up.
+/// `swap` has to exchange the two given elements and is called for every move.
+pub(crate) fn sift_down<T, F, S>(
+    elements: &mut [T],
+    mut node: usize,
+    is_less: &mut F,
+    swap: &mut S,
+) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+    S: FnMut(&mut [T], usize, usize),
+{
+    loop {
+        let mut child = 2 * node + 1;
+        if child >= elements.len() {
+            return node;
+        }
+        if child + 1 < elements.len() && is_less(&elements[child], &elements[child + 1]) {
+            child += 1;
+        }
+        if !is_less(&elements[node], &elements[child]) {
+            return node;
+        }
+        swap(elements, node, child);
+        node = child;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_heap_pops_in_descending_order() {
+        let mut heap = BinaryHeap::new_max();
+        for value in [5, 1, 8, 3, 9, 2, 8] {
+            heap.push(value);
+        }
+        assert_eq!(heap.len(), 7);
+        assert_eq!(heap.peek(), Some(&9));
+        assert_eq!(heap.into_sorted_vec(), [9, 8, 8, 5, 3, 2, 1]);
+    }
+
+    #[test]
+    fn test_min_heap_pops_in_ascending_order() {
+        let mut heap = BinaryHeap::new_min();
+        for value in (0..1000).map(|i| (i * 7919) % 1000) {
+            heap.push(value);
+        }
+        let mut popped = Vec::new();
+        while let Some(value) = heap.pop() {
+            popped.push(value);
+        }
+        assert_eq!(popped, (0..1000).collect::<Vec<_>>());
+        assert!(heap.is_empty());
+        assert_eq!(heap.pop(), None);
+    }
+
+    #[test]
+    fn test_comparator_heap() {
+        let mut heap =
+            BinaryHeap::with_comparator(|a: &(&str, u32), b: &(&str, u32)| a.1.cmp(&b.1));
+        heap.push(("low", 1));
+        heap.push(("high", 10));
+        heap.push(("mid", 5));
+        assert_eq!(heap.pop(), Some(("high", 10)));
+        assert_eq!(heap.pop(), Some(("mid", 5)));
+    }
+
+    #[test]
+    fn test_decrease_key_on_min_heap() {
+        let mut heap = BinaryHeap::new_min();
+        let handles: Vec<HeapHandle> = (10..20).map(|value| heap.push(value)).collect();
+        assert_eq!(heap.update(handles[7], 3), Ok(17));
+        assert_eq!(heap.get(handles[7]), Some(&3));
+        assert_eq!(heap.peek(), Some(&3));
+
+        // increasing a key moves the element away from the top again
+        assert_eq!(heap.update(handles[7], 30), Ok(3));
+        assert_eq!(heap.pop(), Some(10));
+        assert_eq!(heap.update(handles[0], 1), Err(1));
+        assert_eq!(heap.into_sorted_vec(), [11, 12, 13, 14, 15, 16, 18, 19, 30]);
+    }
+
+    #[test]
+    fn test_remove_by_handle() {
+        let mut heap = BinaryHeap::new_max();
+        let handles: Vec<HeapHandle> = (0..50).map(|value| heap.push(value)).collect();
+        for handle in handles.iter().step_by(2) {
+            assert!(heap.remove(*handle).is_some());
+        }
+        assert_eq!(heap.remove(handles[0]), None);
+        assert_eq!(heap.get(handles[0]), None);
+        assert_eq!(heap.get(handles[1]), Some(&1));
+        let expected: Vec<i32> = (0..50).rev().filter(|value| value % 2 == 1).collect();
+        assert_eq!(heap.into_sorted_vec(), expected);
+    }
+
+    #[test]
+    fn test_handles_follow_their_elements() {
+        let mut heap = BinaryHeap::new_min();
+        let values: Vec<i32> = (0..300).map(|i| (i * 31) % 300).collect();
+        let handles: Vec<HeapHandle> = values.iter().map(|value| heap.push(*value)).collect();
+        for (i, handle) in handles.iter().enumerate() {
+            if i % 3 == 0 {
+                heap.update(*handle, values[i] - 1000).unwrap();
+            }
+        }
+        for (i, handle) in handles.iter().enumerate() {
+            let expected = if i % 3 == 0 {
+                values[i] - 1000
+            } else {
+                values[i]
+            };
+            assert_eq!(heap.get(*handle), Some(&expected));
+        }
+        let sorted = heap.into_sorted_vec();
+        assert!(sorted.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn test_slots_of_removed_elements_are_reused() {
+        let mut heap = BinaryHeap::new_min();
+        for i in 0..10_000 {
+            heap.push(i);
+            heap.push(i + 1);
+            heap.pop();
+        }
+        assert_eq!(heap.len(), 10_000);
+        assert!(heap.slots.len() <= 10_001);
+
+        let mut heap = BinaryHeap::new_max();
+        for _ in 0..1000 {
+            let handle = heap.push(1);
+            heap.pop();
+            assert_eq!(heap.get(handle), None);
+        }
+        assert_eq!(heap.slots.len(), 1);
+    }
+
+    #[test]
+    fn test_stale_handles_do_not_reach_new_elements() {
+        let mut heap = BinaryHeap::new_max();
+        let stale = heap.push(1);
+        assert_eq!(heap.remove(stale), Some(1));
+        let fresh = heap.push(2);
+        assert_ne!(stale, fresh);
+        assert_eq!(heap.get(stale), None);
+        assert_eq!(heap.update(stale, 5), Err(5));
+        assert_eq!(heap.remove(stale), None);
+        assert_eq!(heap.get(fresh), Some(&2));
+        assert_eq!(heap.pop(), Some(2));
+    }
+}
@@ -0,0 +1,3 @@
+mod binary_heap;
+pub use binary_heap::{BinaryHeap, HeapHandle};
+pub(crate) use binary_heap::{sift_down, sift_up};
@@ -12,9 +12,9 @@ pub fn gale_shapley(
         return Err(GaleShapleyError::DifferentSetSize);
     }
 
-    let mut proposed_index: Vec<_> = std::iter::repeat(0).take(a.len()).collect();
-    let mut matched_a: Vec<Option<usize>> = std::iter::repeat(None).take(a.len()).collect();
-    let mut matched_b: Vec<Option<usize>> = std::iter::repeat(None).take(a.len()).collect();
+    let mut proposed_index: Vec<_> = std::iter::repeat_n(0, a.len()).collect();
+    let mut matched_a: Vec<Option<usize>> = std::iter::repeat_n(None, a.len()).collect();
+    let mut matched_b: Vec<Option<usize>> = std::iter::repeat_n(None, a.len()).collect();
     let mut free_a: Vec<_> = (0..a.len()).collect();
 
     while !free_a.is_empty() {
@@ -133,7 +133,7 @@ mod test {
         let b = [vec![0], vec![0]];
         let result = gale_shapley(&a, &b);
         assert!(result.is_ok());
-        let result: HashSet<_> = HashSet::from_iter(result.unwrap().into_iter());
+        let result: HashSet<_> = HashSet::from_iter(result.unwrap());
         assert_eq!(result, HashSet::from([(1, 0), (0, 1)]));
     }
 
@@ -143,14 +143,14 @@ mod test {
         let b = [vec![0, 2, 1], vec![0, 1, 2], vec![2, 1, 0]];
         let result = gale_shapley(&a, &b);
         assert!(result.is_ok());
-        let result: HashSet<_> = HashSet::from_iter(result.unwrap().into_iter());
+        let result: HashSet<_> = HashSet::from_iter(result.unwrap());
         assert_eq!(result, HashSet::from([(0, 0), (1, 1), (2,2)]));
     }
 
     fn gen_preferences(num_elements: usize) -> Vec<usize> {
         let mut result: Vec<usize> = (0..num_elements).collect();
         result.shuffle(&mut rand::thread_rng());
-        return result;
+        result
     }
 
     fn gen_preference_list(num_elements: usize) -> Vec<Vec<usize>> {
@@ -187,8 +187,8 @@ mod test {
             matches.iter().map(|(_, b)| b)
         ));
 
-        let mut match_for_a: Vec<_> = std::iter::repeat(0).take(a.len()).collect();
-        let mut match_for_b: Vec<_> = std::iter::repeat(0).take(a.len()).collect();
+        let mut match_for_a: Vec<_> = std::iter::repeat_n(0, a.len()).collect();
+        let mut match_for_b: Vec<_> = std::iter::repeat_n(0, a.len()).collect();
 
         for (a, b) in matches {
             match_for_a[*a] = *b;
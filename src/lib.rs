@@ -1,4 +1,8 @@
 pub mod sorting;
 pub mod general_combinatorial;
 
-pub mod prng;
\ No newline at end of file
+pub mod prng;
+
+pub mod collections;
+
+pub mod strings;
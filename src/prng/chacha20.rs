@@ -30,21 +30,15 @@ impl<const ROUNDS: usize> ChaChaGenerator<ROUNDS> {
     }
 
     fn perform_rounds(&mut self) {
-        let working_vec: &mut [u32; 16] = unsafe { std::mem::transmute(&mut self.random_bytes) };
-        // use random bytes as working state
-        *working_vec = init_state(&self.key, self.counter, &self.nonce);
+        let mut working_vec = init_state(&self.key, self.counter, &self.nonce);
         for _ in 0..(ROUNDS / 2) {
-            chacha_round(working_vec);
-        }
-        // if we are on a big endian system we need to flip each of the u32
-        #[cfg(target_endian = "big")]
-        for i in 0..16 {
-            working_vec[i] = u32::to_le(working_vec[i]);
+            chacha_round(&mut working_vec);
         }
 
         let to_add = init_state(&self.key, self.counter, &self.nonce);
-        for i in 0..16 {
-            working_vec[i] = working_vec[i].wrapping_add(to_add[i]);
+        for (i, word) in working_vec.iter().enumerate() {
+            let word = word.wrapping_add(to_add[i]);
+            self.random_bytes[i * 4..i * 4 + 4].copy_from_slice(&word.to_le_bytes());
         }
 
         self.counter += 1;
@@ -73,11 +67,11 @@ fn init_state(key: &[u8; 32], counter: u32, nonce: &[u8; 12]) -> [u32; 16] {
     state[3] = 0x6b206574;
 
     for i in 0..8 {
-        state[i + 4] = u32::from_le(unsafe { *(&key[i * 4] as *const u8 as *const u32) });
+        state[i + 4] = u32::from_le_bytes(key[i * 4..i * 4 + 4].try_into().unwrap());
     }
     state[12] = counter;
     for i in 0..3 {
-        state[i + 13] = u32::from_le(unsafe { *(&nonce[i * 4] as *const u8 as *const u32) });
+        state[i + 13] = u32::from_le_bytes(nonce[i * 4..i * 4 + 4].try_into().unwrap());
     }
     state
 }
@@ -171,10 +165,10 @@ mod test {
 
     #[test]
     fn test_rfc7539_example_2_3_2() {
-        let mut generator = ChaChaGenerator::<20>::from_key(KEY.clone(), NONCE.clone());
+        let mut generator = ChaChaGenerator::<20>::from_key(KEY, NONCE);
         let mut result: [u8; 64] = [0; 64];
-        for i in 0..64 {
-            result[i] = generator.next_byte();
+        for byte in result.iter_mut() {
+            *byte = generator.next_byte();
         }
         let expected = [
             0xe4e7f110, 0x15593bd1, 0x1fdd0f50, 0xc47120a3, 0xc7f4d1c7, 0x0368c033, 0x9aaa2204,
@@ -187,5 +181,52 @@ mod test {
         );
     }
 
-    
+    /// RFC 8439 appendix A.1, test vector #2 followed by the next block of the same key stream
+    #[test]
+    fn test_rfc8439_zero_key_stream() {
+        let mut generator = ChaChaGenerator::<20>::from_key([0; 32], [0; 12]);
+        let mut result = [0u8; 128];
+        for byte in result.iter_mut() {
+            *byte = generator.next_byte();
+        }
+        let expected: [u8; 128] = [
+            0x9f, 0x07, 0xe7, 0xbe, 0x55, 0x51, 0x38, 0x7a, 0x98, 0xba, 0x97, 0x7c,
+            0x73, 0x2d, 0x08, 0x0d, 0xcb, 0x0f, 0x29, 0xa0, 0x48, 0xe3, 0x65, 0x69,
+            0x12, 0xc6, 0x53, 0x3e, 0x32, 0xee, 0x7a, 0xed, 0x29, 0xb7, 0x21, 0x76,
+            0x9c, 0xe6, 0x4e, 0x43, 0xd5, 0x71, 0x33, 0xb0, 0x74, 0xd8, 0x39, 0xd5,
+            0x31, 0xed, 0x1f, 0x28, 0x51, 0x0a, 0xfb, 0x45, 0xac, 0xe1, 0x0a, 0x1f,
+            0x4b, 0x79, 0x4d, 0x6f, 0x2d, 0x09, 0xa0, 0xe6, 0x63, 0x26, 0x6c, 0xe1,
+            0xae, 0x7e, 0xd1, 0x08, 0x19, 0x68, 0xa0, 0x75, 0x8e, 0x71, 0x8e, 0x99,
+            0x7b, 0xd3, 0x62, 0xc6, 0xb0, 0xc3, 0x46, 0x34, 0xa9, 0xa0, 0xb3, 0x5d,
+            0x01, 0x27, 0x37, 0x68, 0x1f, 0x7b, 0x5d, 0x0f, 0x28, 0x1e, 0x3a, 0xfd,
+            0xe4, 0x58, 0xbc, 0x1e, 0x73, 0xd2, 0xd3, 0x13, 0xc9, 0xcf, 0x94, 0xc0,
+            0x5f, 0xf3, 0x71, 0x62, 0x40, 0xa2, 0x48, 0xf2,
+        ];
+        assert_eq!(result, expected);
+    }
+
+    /// RFC 8439 appendix A.1, test vector #4: the key bytes are read in little endian order
+    #[test]
+    fn test_rfc8439_key_byte_order() {
+        let mut key = [0; 32];
+        key[1] = 0xff;
+        let mut generator = ChaChaGenerator::<20>::from_key(key, [0; 12]);
+        // the generator starts at block 1, the test vector is block 2
+        for _ in 0..64 {
+            generator.next_byte();
+        }
+        let mut result = [0u8; 64];
+        for byte in result.iter_mut() {
+            *byte = generator.next_byte();
+        }
+        let expected: [u8; 64] = [
+            0x72, 0xd5, 0x4d, 0xfb, 0xf1, 0x2e, 0xc4, 0x4b, 0x36, 0x26, 0x92, 0xdf,
+            0x94, 0x13, 0x7f, 0x32, 0x8f, 0xea, 0x8d, 0xa7, 0x39, 0x90, 0x26, 0x5e,
+            0xc1, 0xbb, 0xbe, 0xa1, 0xae, 0x9a, 0xf0, 0xca, 0x13, 0xb2, 0x5a, 0xa2,
+            0x6c, 0xb4, 0xa6, 0x48, 0xcb, 0x9b, 0x9d, 0x1b, 0xe6, 0x5b, 0x2c, 0x09,
+            0x24, 0xa6, 0x6c, 0x54, 0xd5, 0x45, 0xec, 0x1b, 0x73, 0x74, 0xf4, 0x87,
+            0x2e, 0x99, 0xf0, 0x96,
+        ];
+        assert_eq!(result, expected);
+    }
 }
@@ -1,3 +1,53 @@
 pub trait PrnGenerator {
     fn next_byte(&mut self) -> u8;
+
+    /// Combines the next eight bytes into a little endian u64
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        for byte in bytes.iter_mut() {
+            *byte = self.next_byte();
+        }
+        u64::from_le_bytes(bytes)
+    }
+
+    /// Returns a uniformly distributed number in `0..bound`. `bound` must not be 0.
+    fn next_below(&mut self, bound: u64) -> u64 {
+        assert!(bound > 0, "bound must be positive");
+        // reject the values of the incomplete last block so every result is equally likely
+        let zone = u64::MAX - u64::MAX % bound;
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return value % bound;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PrnGenerator;
+
+    struct CountingGenerator(u8);
+
+    impl PrnGenerator for CountingGenerator {
+        fn next_byte(&mut self) -> u8 {
+            self.0 = self.0.wrapping_add(1);
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_next_u64_is_little_endian() {
+        let mut generator = CountingGenerator(0);
+        assert_eq!(generator.next_u64(), 0x0807060504030201);
+    }
+
+    #[test]
+    fn test_next_below_stays_in_bounds() {
+        let mut generator = CountingGenerator(0);
+        for bound in 1..100 {
+            assert!(generator.next_below(bound) < bound);
+        }
+    }
 }
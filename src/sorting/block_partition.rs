@@ -0,0 +1,213 @@
+use std::cmp::Ordering;
+
+use super::heapsort::heapsort_impl;
+use super::insertionsort::insertionsort_impl;
+use super::instrumented::NoProbe;
+use super::pivot::{MedianOfThree, PivotStrategy};
+
+/// Partitions at or below this length are handed to insertion sort
+const INSERTION_SORT_THRESHOLD: usize = 16;
+
+/// The number of elements that are classified before any of them is moved. Offsets within a block are
+/// stored as `u8`, so it must not be larger than 256.
+const BLOCK: usize = 128;
+
+/// Sorts the elements with BlockQuicksort. Partitioning first compares a block of elements against the
+/// pivot and only stores the offsets of the misplaced ones, without branching on the result. Then the
+/// misplaced elements of both sides are swapped. This avoids the branch mispredictions of `quicksort` on
+/// random data. Like `introsort` it switches to heapsort on adversarial inputs. Not stable.
+pub fn block_quicksort<T: PartialOrd>(elements: &mut [T]) {
+    block_quicksort_impl(elements, &mut |a: &T, b: &T| a < b);
+}
+
+/// Like `block_quicksort`, but with a comparator function
+pub fn block_quicksort_by<T, F: FnMut(&T, &T) -> Ordering>(elements: &mut [T], mut compare: F) {
+    block_quicksort_impl(elements, &mut |a: &T, b: &T| {
+        compare(a, b) == Ordering::Less
+    });
+}
+
+/// Like `block_quicksort`, but ordered by the key extracted with `key`
+pub fn block_quicksort_by_key<T, K: PartialOrd, F: FnMut(&T) -> K>(elements: &mut [T], mut key: F) {
+    block_quicksort_impl(elements, &mut |a: &T, b: &T| key(a) < key(b));
+}
+
+fn block_quicksort_impl<T, F: FnMut(&T, &T) -> bool>(elements: &mut [T], is_less: &mut F) {
+    let depth_limit = 2 * (usize::BITS - elements.len().leading_zeros());
+    block_quicksort_loop(elements, is_less, depth_limit);
+}
+
+fn block_quicksort_loop<T, F: FnMut(&T, &T) -> bool>(
+    mut elements: &mut [T],
+    is_less: &mut F,
+    mut depth_limit: u32,
+) {
+    loop {
+        if elements.len() <= INSERTION_SORT_THRESHOLD {
+            insertionsort_impl(elements, is_less, &mut NoProbe);
+            return;
+        }
+        if depth_limit == 0 {
+            heapsort_impl(elements, is_less, &mut NoProbe);
+            return;
+        }
+        depth_limit -= 1;
+
+        let pivot_index = MedianOfThree.select_pivot(elements, is_less);
+        elements.swap(0, pivot_index);
+        let (pivot, rest) = elements.split_at_mut(1);
+        let mid = block_partition(rest, &pivot[0], &mut |element, pivot| {
+            is_less(element, pivot)
+        });
+        let mut equal_end = mid + 1;
+        if mid == 0 {
+            // the pivot is the smallest element, so copies of it would all end up on the right side again.
+            // Gathering them next to the pivot finishes them at once, which keeps slices with many equal
+            // elements fast.
+            equal_end += block_partition(rest, &pivot[0], &mut |element, pivot| {
+                !is_less(pivot, element)
+            });
+        } else {
+            // the last element less than the pivot takes the place of the pivot
+            elements.swap(0, mid);
+        }
+
+        let (left, rest) = elements.split_at_mut(mid);
+        let right = &mut rest[equal_end - mid..];
+        let (smaller, larger) = if left.len() <= right.len() {
+            (left, right)
+        } else {
+            (right, left)
+        };
+        block_quicksort_loop(smaller, is_less, depth_limit);
+        elements = larger;
+    }
+}
+
+/// Moves the elements for which `goes_left(element, pivot)` is true to the front and returns how many
+/// there are
+pub(crate) fn block_partition<T, F: FnMut(&T, &T) -> bool>(
+    elements: &mut [T],
+    pivot: &T,
+    goes_left: &mut F,
+) -> usize {
+    // elements[..l] go left and elements[r..] go right, the blocks at l and r are being worked on
+    let mut l = 0;
+    let mut r = elements.len();
+    let mut offsets_l = [0u8; BLOCK];
+    let mut offsets_r = [0u8; BLOCK];
+    // the offsets of the misplaced elements of each block that are not swapped yet
+    let (mut start_l, mut count_l) = (0, 0);
+    let (mut start_r, mut count_r) = (0, 0);
+
+    while r - l >= 2 * BLOCK {
+        if count_l == 0 {
+            start_l = 0;
+            for i in 0..BLOCK {
+                // the offset is always written and only kept if the element is misplaced, so there is
+                // no branch that depends on the comparison
+                offsets_l[count_l] = i as u8;
+                count_l += !goes_left(&elements[l + i], pivot) as usize;
+            }
+        }
+        if count_r == 0 {
+            start_r = 0;
+            for i in 0..BLOCK {
+                offsets_r[count_r] = i as u8;
+                count_r += goes_left(&elements[r - 1 - i], pivot) as usize;
+            }
+        }
+
+        let count = usize::min(count_l, count_r);
+        for j in 0..count {
+            elements.swap(
+                l + offsets_l[start_l + j] as usize,
+                r - 1 - offsets_r[start_r + j] as usize,
+            );
+        }
+        start_l += count;
+        start_r += count;
+        count_l -= count;
+        count_r -= count;
+        if count_l == 0 {
+            l += BLOCK;
+        }
+        if count_r == 0 {
+            r -= BLOCK;
+        }
+    }
+
+    // the rest is shorter than two blocks and partitioned element by element. Blocks with misplaced
+    // elements left are still part of it.
+    loop {
+        while l < r && goes_left(&elements[l], pivot) {
+            l += 1;
+        }
+        while l < r && !goes_left(&elements[r - 1], pivot) {
+            r -= 1;
+        }
+        if l >= r {
+            return l;
+        }
+        r -= 1;
+        elements.swap(l, r);
+        l += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sorting::test_helpers::{
+        self, antiqsort, is_sorted, test_panic_safety, test_unstable_sort,
+    };
+
+    use super::*;
+
+    test_helpers::basic_sorting_tests!(block_quicksort);
+
+    #[test]
+    fn test_block_quicksort_with_random_items() {
+        test_unstable_sort(&block_quicksort, 10_000, -1000, 1000);
+        test_unstable_sort(&block_quicksort, 10_000, 0, 3);
+    }
+
+    #[test]
+    fn test_block_quicksort_conformance() {
+        test_helpers::test_sort_conformance(block_quicksort::<u64>);
+        test_helpers::test_sort_conformance(block_quicksort::<String>);
+        test_helpers::test_sort_conformance(|items: &mut [f32]| {
+            block_quicksort_by(items, |a, b| a.partial_cmp(b).unwrap())
+        });
+    }
+
+    #[test]
+    fn test_block_partition() {
+        let mut items: Vec<u32> = (0..1000).map(|i| (i * 7919) % 1000).collect();
+        let mid = block_partition(&mut items, &500, &mut |a, b| a < b);
+        assert_eq!(mid, 500);
+        assert!(items[..mid].iter().all(|item| *item < 500));
+        assert!(items[mid..].iter().all(|item| *item >= 500));
+    }
+
+    #[test]
+    fn test_block_quicksort_by_key() {
+        let mut items: Vec<(u8, char)> = "the quick brown fox"
+            .chars()
+            .map(|c| (c as u8 % 7, c))
+            .collect();
+        block_quicksort_by_key(&mut items, |item| item.0);
+        assert!(is_sorted(items.iter().map(|item| item.0)));
+    }
+
+    #[test]
+    fn test_block_quicksort_is_panic_safe() {
+        test_panic_safety(block_quicksort, 500);
+    }
+
+    #[test]
+    fn test_block_quicksort_resists_adversaries() {
+        let len = 2000;
+        let (_, comparisons) = antiqsort(len, |items, compare| block_quicksort_by(items, compare));
+        assert!(comparisons < 6 * len * len.ilog2() as usize);
+    }
+}
@@ -1,15 +1,54 @@
+use std::cmp::Ordering;
+
+use super::instrumented::{NoProbe, SortProbe};
+
 pub fn bubblesort<T: PartialOrd>(elements: &mut [T]) {
+    bubblesort_impl(elements, &mut |a: &T, b: &T| a < b, &mut NoProbe);
+}
+
+/// Sorts the elements with a comparator function, keeping the order of equal elements.
+/// If `compare` panics, the elements are left in some permutation of the input.
+pub fn bubblesort_by<T, F: FnMut(&T, &T) -> Ordering>(elements: &mut [T], mut compare: F) {
+    bubblesort_impl(
+        elements,
+        &mut |a: &T, b: &T| compare(a, b) == Ordering::Less,
+        &mut NoProbe,
+    );
+}
+
+/// Sorts the elements by the key extracted with `key`. The key is recomputed on every comparison.
+pub fn bubblesort_by_key<T, K: PartialOrd, F: FnMut(&T) -> K>(elements: &mut [T], mut key: F) {
+    bubblesort_impl(elements, &mut |a: &T, b: &T| key(a) < key(b), &mut NoProbe);
+}
+
+/// Sorts the elements by the key extracted with `key`, calling `key` only once per element.
+pub fn bubblesort_by_cached_key<T, K: PartialOrd, F: FnMut(&T) -> K>(elements: &mut [T], key: F) {
+    super::cached_key::sort_by_cached_key(elements, key, |keyed| {
+        bubblesort_impl(
+            keyed,
+            &mut |a: &(K, usize), b: &(K, usize)| a.0 < b.0,
+            &mut NoProbe,
+        )
+    });
+}
+
+pub(crate) fn bubblesort_impl<T, F: FnMut(&T, &T) -> bool, P: SortProbe>(
+    elements: &mut [T],
+    is_less: &mut F,
+    probe: &mut P,
+) {
     if elements.len() <= 1 {
         return;
     }
     let mut swapped = true;
     let mut n = elements.len() - 1;
-    while swapped {
+    while swapped && n > 0 {
         swapped = false;
         for i in 0..n {
-            if elements[i] > elements[i + 1] {
+            if is_less(&elements[i + 1], &elements[i]) {
                 let (left, right) = elements.split_at_mut(i + 1);
                 std::mem::swap(&mut left[i], &mut right[0]);
+                probe.swapped(elements, i, i + 1);
                 swapped = true;
             }
         }
@@ -19,9 +58,9 @@ pub fn bubblesort<T: PartialOrd>(elements: &mut [T]) {
 
 #[cfg(test)]
 mod tests {
-    use crate::sorting::test_helpers::{is_stabelly_sorted, random_comparable_list, self};
+    use crate::sorting::test_helpers::{is_stabelly_sorted, random_comparable_list, test_panic_safety, self};
 
-    use super::bubblesort;
+    use super::{bubblesort, bubblesort_by, bubblesort_by_cached_key, bubblesort_by_key};
 
     test_helpers::basic_sorting_tests!(bubblesort);
 
@@ -33,4 +72,55 @@ mod tests {
         bubblesort(&mut data);
         assert!(is_stabelly_sorted(&data));
     }
+
+    #[test]
+    fn it_sorts_reversed_slices() {
+        let mut data = [5, 4, 3, 2, 1];
+        bubblesort(&mut data);
+        assert_eq!(data, [1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn it_sorts_descending_with_a_comparator() {
+        let mut data = [3, 1, 4, 1, 5, 9, 2, 6];
+        bubblesort_by(&mut data, |a, b| b.cmp(a));
+        assert_eq!(data, [9, 6, 5, 4, 3, 2, 1, 1]);
+    }
+
+    #[test]
+    fn it_sorts_stabelly_by_key() {
+        let mut data: Vec<(i32, usize)> = [2, 0, 1, 0, 2, 1].iter().cloned().zip(0..).collect();
+        bubblesort_by_key(&mut data, |item| item.0);
+        assert_eq!(data, [(0, 1), (0, 3), (1, 2), (1, 5), (2, 0), (2, 4)]);
+    }
+
+    #[test]
+    fn it_sorts_stabelly_by_cached_key() {
+        let mut data = random_comparable_list(50, 0, 10);
+        let mut key_calls = 0;
+        bubblesort_by_cached_key(&mut data, |item| {
+            key_calls += 1;
+            item.value()
+        });
+        assert!(is_stabelly_sorted(&data));
+        assert_eq!(key_calls, 50);
+    }
+
+    #[test]
+    fn it_keeps_all_elements_with_nans() {
+        let mut data = [2.0, f64::NAN, 1.0, f64::NAN, 0.0];
+        bubblesort(&mut data);
+        assert_eq!(data.iter().filter(|item| item.is_nan()).count(), 2);
+    }
+
+    #[test]
+    fn it_is_panic_safe() {
+        test_panic_safety(bubblesort, 30);
+        test_panic_safety(|items| bubblesort_by(items, |a, b| a.partial_cmp(b).unwrap()), 30);
+    }
+
+    #[test]
+    fn test_bubblesort_conformance() {
+        test_helpers::test_stable_sort_conformance(bubblesort);
+    }
 }
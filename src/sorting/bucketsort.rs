@@ -0,0 +1,130 @@
+use super::countingsort::{check_memory_limit, BoundedKeyError};
+use super::insertionsort::insertionsort_impl;
+use super::instrumented::NoProbe;
+use super::permutation::apply_permutation_in_place;
+
+/// Sorts floats in [0, 1) with one bucket per element, see `bucket_sort_with`
+pub fn bucket_sort<T: Copy + Into<f64>>(elements: &mut [T]) -> Result<(), BoundedKeyError> {
+    bucket_sort_with(elements, elements.len())
+}
+
+/// Sorts floats in [0, 1) by distributing them over `bucket_count` buckets of the same width and
+/// insertion sorting every bucket. Takes linear time on average if the floats are spread evenly.
+/// Fails without changing the elements if a float is NaN or not in [0, 1).
+pub fn bucket_sort_with<T: Copy + Into<f64>>(
+    elements: &mut [T],
+    bucket_count: usize,
+) -> Result<(), BoundedKeyError> {
+    bucket_sort_by_key(elements, bucket_count, |element| (*element).into())
+}
+
+/// Sorts the elements stabelly like `bucket_sort_with` by the key in [0, 1) extracted with `key`,
+/// which is called once per element
+pub fn bucket_sort_by_key<T, F: FnMut(&T) -> f64>(
+    elements: &mut [T],
+    bucket_count: usize,
+    key: F,
+) -> Result<(), BoundedKeyError> {
+    let keys: Vec<f64> = elements.iter().map(key).collect();
+    if !keys.iter().all(|key| (0.0..1.0).contains(key)) {
+        return Err(BoundedKeyError::KeyOutOfRange);
+    }
+    if keys.is_empty() {
+        return Ok(());
+    }
+    let bucket_count = usize::max(bucket_count, 1);
+    check_memory_limit(bucket_count as u128, std::mem::size_of::<Vec<usize>>())?;
+
+    let mut buckets: Vec<Vec<usize>> = vec![Vec::new(); bucket_count];
+    for (i, key) in keys.iter().enumerate() {
+        let bucket = usize::min((key * bucket_count as f64) as usize, bucket_count - 1);
+        buckets[bucket].push(i);
+    }
+    let mut order = Vec::with_capacity(keys.len());
+    for mut bucket in buckets {
+        insertionsort_impl(
+            &mut bucket,
+            &mut |a: &usize, b: &usize| keys[*a] < keys[*b],
+            &mut NoProbe,
+        );
+        order.append(&mut bucket);
+    }
+    apply_permutation_in_place(elements, &order);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use crate::sorting::test_helpers::{
+        self, is_sorted, is_stabelly_sorted, random_comparable_list,
+    };
+
+    use super::*;
+
+    /// Maps the test values to [0, 1) without changing their order
+    fn unit_interval(value: f64) -> f64 {
+        value / 1024.0
+    }
+
+    fn bucket_sort_i32(elements: &mut [i32]) {
+        bucket_sort_by_key(elements, 4, |element| unit_interval(*element as f64)).unwrap();
+    }
+
+    test_helpers::basic_sorting_tests!(bucket_sort_i32);
+
+    #[test]
+    fn test_bucket_sort_conformance() {
+        for bucket_count in [1, 10, 1000] {
+            test_helpers::test_sort_conformance(|items: &mut [f64]| {
+                bucket_sort_by_key(items, bucket_count, |item| unit_interval(*item)).unwrap()
+            });
+            test_helpers::test_stable_sort_conformance(|items| {
+                bucket_sort_by_key(items, bucket_count, |item| {
+                    unit_interval(item.value() as f64)
+                })
+                .unwrap()
+            });
+        }
+    }
+
+    #[test]
+    fn test_bucket_sort_random_floats() {
+        let mut generator = rand::thread_rng();
+        let mut items: Vec<f64> = (0..1000).map(|_| generator.gen()).collect();
+        bucket_sort(&mut items).unwrap();
+        assert!(is_sorted(items.iter()));
+
+        let mut items: Vec<f32> = (0..1000).map(|_| generator.gen()).collect();
+        bucket_sort_with(&mut items, 7).unwrap();
+        assert!(is_sorted(items.iter()));
+    }
+
+    #[test]
+    fn test_bucket_sort_by_key_is_stable() {
+        let mut data = random_comparable_list(300, 0, 50);
+        bucket_sort_by_key(&mut data, 16, |item| item.value() as f64 / 64.0).unwrap();
+        assert!(is_stabelly_sorted(&data));
+    }
+
+    #[test]
+    fn test_bucket_sort_rejects_keys_out_of_range() {
+        for invalid in [1.0, -0.5, f64::NAN, f64::INFINITY] {
+            let mut items = [0.5, invalid, 0.25];
+            assert_eq!(bucket_sort(&mut items), Err(BoundedKeyError::KeyOutOfRange));
+            assert_eq!(items[0], 0.5);
+            assert_eq!(items[2], 0.25);
+        }
+    }
+
+    #[test]
+    fn test_bucket_sort_rejects_too_many_buckets() {
+        let mut items = [0.5, 0.25];
+        assert_eq!(
+            bucket_sort_with(&mut items, usize::MAX),
+            Err(BoundedKeyError::ExceedsMemoryLimit)
+        );
+        assert_eq!(items, [0.5, 0.25]);
+    }
+}
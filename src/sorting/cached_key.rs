@@ -0,0 +1,17 @@
+use super::permutation::apply_permutation_in_place;
+
+/// Sorts the elements by a key that is only computed once per element.
+/// `sort` receives the keys together with the initial index of their element and has to order them,
+/// afterwards the elements are moved into the same order.
+pub(crate) fn sort_by_cached_key<T, K, F, S>(elements: &mut [T], key: F, sort: S)
+where
+    F: FnMut(&T) -> K,
+    S: FnOnce(&mut [(K, usize)]),
+{
+    let mut keyed: Vec<(K, usize)> = elements.iter().map(key).zip(0..).collect();
+    sort(&mut keyed);
+
+    // keyed[i].1 holds the index the element for position i initially came from
+    let permutation: Vec<usize> = keyed.into_iter().map(|(_, index)| index).collect();
+    apply_permutation_in_place(elements, &permutation);
+}
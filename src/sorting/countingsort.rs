@@ -0,0 +1,200 @@
+use super::permutation::apply_permutation_in_place;
+use super::radixsort::RadixKey;
+
+/// The most memory the counters, holes or buckets of the bounded key sorts may take, 64 MiB
+pub const BOUNDED_KEY_MEMORY_LIMIT: usize = 64 << 20;
+
+/// Why a bounded key sort refused to sort. The elements are left unchanged in both cases.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum BoundedKeyError {
+    /// The keys span too many values, so one counter, hole or bucket for each of them would take more than
+    /// `BOUNDED_KEY_MEMORY_LIMIT` bytes
+    ExceedsMemoryLimit,
+    /// A key is outside of the range the sort supports, such as a float that is NaN or not in [0, 1)
+    KeyOutOfRange,
+}
+
+/// Sorts integers by counting how often each value occurs. Takes O(n + k) time and memory for the range
+/// k of the values, so it beats comparison sorts on many small values like ratings.
+pub fn counting_sort<T: RadixKey>(elements: &mut [T]) -> Result<(), BoundedKeyError> {
+    counting_sort_by_key(elements, |element| *element)
+}
+
+/// Sorts the elements stabelly by the integer key extracted with `key`, which is called once per element
+pub fn counting_sort_by_key<T, K: RadixKey, F: FnMut(&T) -> K>(
+    elements: &mut [T],
+    key: F,
+) -> Result<(), BoundedKeyError> {
+    let (keys, min, range) = bounded_keys(elements, key, std::mem::size_of::<usize>())?;
+
+    let mut starts = vec![0; range];
+    for key in &keys {
+        starts[(key - min) as usize] += 1;
+    }
+    let mut start = 0;
+    for count in starts.iter_mut() {
+        let next = start + *count;
+        *count = start;
+        start = next;
+    }
+
+    let mut order = vec![0; keys.len()];
+    for (i, key) in keys.iter().enumerate() {
+        let position = &mut starts[(key - min) as usize];
+        order[*position] = i;
+        *position += 1;
+    }
+    apply_permutation_in_place(elements, &order);
+    Ok(())
+}
+
+/// Sorts integers by putting every element into the hole for its value and reading the holes in order.
+/// Like `counting_sort`, but collects the elements in one pass instead of counting them first.
+pub fn pigeonhole_sort<T: RadixKey>(elements: &mut [T]) -> Result<(), BoundedKeyError> {
+    pigeonhole_sort_by_key(elements, |element| *element)
+}
+
+/// Sorts the elements stabelly by the integer key extracted with `key`, which is called once per element
+pub fn pigeonhole_sort_by_key<T, K: RadixKey, F: FnMut(&T) -> K>(
+    elements: &mut [T],
+    key: F,
+) -> Result<(), BoundedKeyError> {
+    let (keys, min, range) = bounded_keys(elements, key, std::mem::size_of::<Vec<usize>>())?;
+
+    let mut holes: Vec<Vec<usize>> = vec![Vec::new(); range];
+    for (i, key) in keys.iter().enumerate() {
+        holes[(key - min) as usize].push(i);
+    }
+    let order: Vec<usize> = holes.into_iter().flatten().collect();
+    apply_permutation_in_place(elements, &order);
+    Ok(())
+}
+
+/// Extracts the keys and returns them with the smallest key and the number of values between the smallest
+/// and the greatest key, after checking that one slot of `slot_size` bytes per value fits into the limit
+fn bounded_keys<T, K: RadixKey, F: FnMut(&T) -> K>(
+    elements: &[T],
+    mut key: F,
+    slot_size: usize,
+) -> Result<(Vec<u64>, u64, usize), BoundedKeyError> {
+    let keys: Vec<u64> = elements
+        .iter()
+        .map(|element| key(element).to_radix_key())
+        .collect();
+    let (Some(&min), Some(&max)) = (keys.iter().min(), keys.iter().max()) else {
+        return Ok((keys, 0, 0));
+    };
+    let range = (max - min) as u128 + 1;
+    check_memory_limit(range, slot_size)?;
+    Ok((keys, min, range as usize))
+}
+
+/// Checks that `slots` slots of `slot_size` bytes each fit into `BOUNDED_KEY_MEMORY_LIMIT`
+pub(crate) fn check_memory_limit(slots: u128, slot_size: usize) -> Result<(), BoundedKeyError> {
+    if slots * slot_size as u128 > BOUNDED_KEY_MEMORY_LIMIT as u128 {
+        Err(BoundedKeyError::ExceedsMemoryLimit)
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sorting::test_helpers::{self, is_stabelly_sorted, random_comparable_list};
+
+    use super::*;
+
+    fn counting_sort_i32(elements: &mut [i32]) {
+        counting_sort(elements).unwrap();
+    }
+
+    fn pigeonhole_sort_i32(elements: &mut [i32]) {
+        pigeonhole_sort(elements).unwrap();
+    }
+
+    mod counting {
+        use super::counting_sort_i32;
+        use crate::sorting::test_helpers;
+
+        test_helpers::basic_sorting_tests!(counting_sort_i32);
+    }
+
+    mod pigeonhole {
+        use super::pigeonhole_sort_i32;
+        use crate::sorting::test_helpers;
+
+        test_helpers::basic_sorting_tests!(pigeonhole_sort_i32);
+    }
+
+    #[test]
+    fn test_counting_sort_conformance() {
+        test_helpers::test_sort_conformance(|items: &mut [u16]| counting_sort(items).unwrap());
+        test_helpers::test_sort_conformance(|items: &mut [i64]| counting_sort(items).unwrap());
+        test_helpers::test_stable_sort_conformance(|items| {
+            counting_sort_by_key(items, |item| item.value()).unwrap()
+        });
+    }
+
+    #[test]
+    fn test_pigeonhole_sort_conformance() {
+        test_helpers::test_sort_conformance(|items: &mut [u8]| pigeonhole_sort(items).unwrap());
+        test_helpers::test_sort_conformance(|items: &mut [isize]| pigeonhole_sort(items).unwrap());
+        test_helpers::test_stable_sort_conformance(|items| {
+            pigeonhole_sort_by_key(items, |item| item.value()).unwrap()
+        });
+    }
+
+    #[test]
+    fn test_negative_keys() {
+        let mut items = [3, -1, i32::MIN + 5, 0, -1, i32::MIN + 2];
+        counting_sort(&mut items).unwrap_err();
+        let mut items = [3, -1, -7, 0, -1, 2];
+        counting_sort(&mut items).unwrap();
+        assert_eq!(items, [-7, -1, -1, 0, 2, 3]);
+        let mut items = [3i8, -1, -128, 127, -1, 2];
+        pigeonhole_sort(&mut items).unwrap();
+        assert_eq!(items, [-128, -1, -1, 2, 3, 127]);
+    }
+
+    #[test]
+    fn test_sorts_by_small_keys() {
+        #[derive(Debug, PartialEq)]
+        struct Review {
+            stars: u8,
+            text: String,
+        }
+        let mut reviews: Vec<Review> = [(5, "great"), (1, "awful"), (5, "fine"), (3, "meh")]
+            .iter()
+            .map(|(stars, text)| Review {
+                stars: *stars,
+                text: text.to_string(),
+            })
+            .collect();
+        counting_sort_by_key(&mut reviews, |review| review.stars).unwrap();
+        let texts: Vec<&str> = reviews.iter().map(|review| review.text.as_str()).collect();
+        assert_eq!(texts, ["awful", "meh", "great", "fine"]);
+
+        let mut data = random_comparable_list(500, -20, 20);
+        pigeonhole_sort_by_key(&mut data, |item| item.value()).unwrap();
+        assert!(is_stabelly_sorted(&data));
+    }
+
+    #[test]
+    fn test_rejects_wide_key_ranges() {
+        let mut items = [u64::MAX, 0, 7];
+        assert_eq!(
+            counting_sort(&mut items),
+            Err(BoundedKeyError::ExceedsMemoryLimit)
+        );
+        assert_eq!(
+            pigeonhole_sort(&mut items),
+            Err(BoundedKeyError::ExceedsMemoryLimit)
+        );
+        assert_eq!(items, [u64::MAX, 0, 7]);
+
+        // the range counts, not the size of the values
+        let mut items = [u64::MAX, u64::MAX - 1000, u64::MAX - 3];
+        counting_sort(&mut items).unwrap();
+        assert_eq!(items, [u64::MAX - 1000, u64::MAX - 3, u64::MAX]);
+    }
+}
@@ -0,0 +1,193 @@
+use std::cmp::Ordering;
+
+use super::heapsort::heapsort_impl;
+use super::insertionsort::insertionsort_impl;
+use super::instrumented::NoProbe;
+use super::quicksort::partition;
+
+/// Partitions at or below this length are handed to insertion sort
+const INSERTION_SORT_THRESHOLD: usize = 16;
+
+/// Sorts the elements with Yaroslavskiy's dual-pivot quicksort, which partitions into three parts around
+/// two pivots at once. Every element is moved less often than with one pivot, which usually makes it
+/// faster than `quicksort` on random data. Like `introsort` it switches to heapsort on adversarial inputs.
+/// Not stable.
+pub fn dual_pivot_quicksort<T: PartialOrd>(elements: &mut [T]) {
+    dual_pivot_impl(elements, &mut |a: &T, b: &T| a < b);
+}
+
+/// Like `dual_pivot_quicksort`, but with a comparator function
+pub fn dual_pivot_quicksort_by<T, F: FnMut(&T, &T) -> Ordering>(
+    elements: &mut [T],
+    mut compare: F,
+) {
+    dual_pivot_impl(elements, &mut |a: &T, b: &T| {
+        compare(a, b) == Ordering::Less
+    });
+}
+
+/// Like `dual_pivot_quicksort`, but ordered by the key extracted with `key`
+pub fn dual_pivot_quicksort_by_key<T, K: PartialOrd, F: FnMut(&T) -> K>(
+    elements: &mut [T],
+    mut key: F,
+) {
+    dual_pivot_impl(elements, &mut |a: &T, b: &T| key(a) < key(b));
+}
+
+fn dual_pivot_impl<T, F: FnMut(&T, &T) -> bool>(elements: &mut [T], is_less: &mut F) {
+    let depth_limit = 2 * (usize::BITS - elements.len().leading_zeros());
+    dual_pivot_loop(elements, is_less, depth_limit);
+}
+
+fn dual_pivot_loop<T, F: FnMut(&T, &T) -> bool>(
+    mut elements: &mut [T],
+    is_less: &mut F,
+    mut depth_limit: u32,
+) {
+    loop {
+        if elements.len() <= INSERTION_SORT_THRESHOLD {
+            insertionsort_impl(elements, is_less, &mut NoProbe);
+            return;
+        }
+        if depth_limit == 0 {
+            heapsort_impl(elements, is_less, &mut NoProbe);
+            return;
+        }
+        depth_limit -= 1;
+
+        let len = elements.len();
+        select_pivots(elements, is_less);
+        if !is_less(&elements[0], &elements[len - 1]) {
+            // both pivots are equal, so there is nothing between them. Partitioning around one pivot
+            // gathers all of its copies, which keeps slices with many equal elements fast.
+            let (lo, hi) = partition(elements, 0, is_less, &mut NoProbe);
+            let (left, rest) = elements.split_at_mut(lo);
+            let right = &mut rest[hi - lo..];
+            let (smaller, larger) = if left.len() <= right.len() {
+                (left, right)
+            } else {
+                (right, left)
+            };
+            dual_pivot_loop(smaller, is_less, depth_limit);
+            elements = larger;
+            continue;
+        }
+
+        let (lt, gt) = partition_dual(elements, is_less);
+        let (left, rest) = elements.split_at_mut(lt);
+        let (middle, right) = rest[1..].split_at_mut(gt - lt - 1);
+        let right = &mut right[1..];
+
+        // recurse into the two smaller parts and continue with the largest one, so the stack stays shallow
+        let mut parts = [left, middle, right];
+        parts.sort_by_key(|part| part.len());
+        let [first, second, largest] = parts;
+        dual_pivot_loop(first, is_less, depth_limit);
+        dual_pivot_loop(second, is_less, depth_limit);
+        elements = largest;
+    }
+}
+
+/// Sorts five evenly spaced samples and moves the second smallest to the front and the second largest to
+/// the back, where they serve as the two pivots
+fn select_pivots<T, F: FnMut(&T, &T) -> bool>(elements: &mut [T], is_less: &mut F) {
+    let len = elements.len();
+    let step = len / 6;
+    let samples = [step, 2 * step, 3 * step, 4 * step, 5 * step];
+    for i in 1..samples.len() {
+        let mut j = i;
+        while j > 0 && is_less(&elements[samples[j]], &elements[samples[j - 1]]) {
+            elements.swap(samples[j], samples[j - 1]);
+            j -= 1;
+        }
+    }
+    elements.swap(0, samples[1]);
+    elements.swap(len - 1, samples[3]);
+}
+
+/// Partitions the elements around the pivots at the front and the back, which have to be in order.
+/// Returns the final indexes of the two pivots: elements left of the first one are less than it,
+/// elements right of the second one are greater than it and the elements in between are neither.
+fn partition_dual<T, F: FnMut(&T, &T) -> bool>(
+    elements: &mut [T],
+    is_less: &mut F,
+) -> (usize, usize) {
+    let last = elements.len() - 1;
+    // elements[1..lt] are less than the first pivot, elements[gt + 1..last] are greater than the second
+    // one and elements[k..=gt] are not looked at yet
+    let mut lt = 1;
+    let mut gt = last - 1;
+    let mut k = 1;
+    while k <= gt {
+        if is_less(&elements[k], &elements[0]) {
+            elements.swap(k, lt);
+            lt += 1;
+        } else if !is_less(&elements[k], &elements[last]) {
+            while k < gt && is_less(&elements[last], &elements[gt]) {
+                gt -= 1;
+            }
+            elements.swap(k, gt);
+            gt -= 1;
+            if is_less(&elements[k], &elements[0]) {
+                elements.swap(k, lt);
+                lt += 1;
+            }
+        }
+        k += 1;
+    }
+    let lt = lt - 1;
+    let gt = gt + 1;
+    elements.swap(0, lt);
+    elements.swap(last, gt);
+    (lt, gt)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sorting::test_helpers::{
+        self, antiqsort, is_sorted, test_panic_safety, test_unstable_sort,
+    };
+
+    use super::*;
+
+    test_helpers::basic_sorting_tests!(dual_pivot_quicksort);
+
+    #[test]
+    fn test_dual_pivot_quicksort_with_random_items() {
+        test_unstable_sort(&dual_pivot_quicksort, 10_000, -1000, 1000);
+        test_unstable_sort(&dual_pivot_quicksort, 10_000, 0, 3);
+    }
+
+    #[test]
+    fn test_dual_pivot_quicksort_conformance() {
+        test_helpers::test_sort_conformance(dual_pivot_quicksort::<u64>);
+        test_helpers::test_sort_conformance(dual_pivot_quicksort::<String>);
+        test_helpers::test_sort_conformance(|items: &mut [f32]| {
+            dual_pivot_quicksort_by(items, |a, b| a.partial_cmp(b).unwrap())
+        });
+    }
+
+    #[test]
+    fn test_dual_pivot_quicksort_by_key() {
+        let mut items: Vec<(u8, char)> = "the quick brown fox"
+            .chars()
+            .map(|c| (c as u8 % 7, c))
+            .collect();
+        dual_pivot_quicksort_by_key(&mut items, |item| item.0);
+        assert!(is_sorted(items.iter().map(|item| item.0)));
+    }
+
+    #[test]
+    fn test_dual_pivot_quicksort_is_panic_safe() {
+        test_panic_safety(dual_pivot_quicksort, 100);
+    }
+
+    #[test]
+    fn test_dual_pivot_quicksort_resists_adversaries() {
+        let len = 2000;
+        let (_, comparisons) = antiqsort(len, |items, compare| {
+            dual_pivot_quicksort_by(items, compare)
+        });
+        assert!(comparisons < 6 * len * len.ilog2() as usize);
+    }
+}
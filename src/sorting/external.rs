@@ -0,0 +1,658 @@
+use std::cmp::Ordering;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+use super::{mergesort_by, quicksort_by};
+use crate::collections::BinaryHeap;
+
+/// The memory budget of `ExternalSortConfig::default()`, 64 MiB
+const DEFAULT_MEMORY_BUDGET: usize = 64 << 20;
+
+/// The number of runs merged at once by `ExternalSortConfig::default()`
+const DEFAULT_MERGE_WIDTH: usize = 64;
+
+/// The largest buffers for reading the input and writing runs while chunks are sorted, the default capacity
+/// of `BufReader`. Larger buffers would only take memory from the records.
+const MAX_CHUNK_BUFFER: usize = 8 << 10;
+
+/// Runs are not read and written with smaller buffers than this, unless the memory budget is too small
+/// for merging two runs with it. Merging fewer runs at once is cheaper than reading them in tiny pieces.
+const MIN_RUN_BUFFER: usize = 4 << 10;
+
+/// Numbers the run files of this process, so concurrent sorts do not try the same file names
+static NEXT_RUN_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Reads and writes the records an external sort works on
+pub trait RecordCodec {
+    type Record;
+
+    /// Writes one record
+    fn encode<W: Write>(&self, record: &Self::Record, out: &mut W) -> io::Result<()>;
+
+    /// Reads the next record, or returns None if the input ended right before it
+    fn decode<R: Read>(&self, input: &mut R) -> io::Result<Option<Self::Record>>;
+
+    /// The number of bytes a record takes in memory, which is counted against the memory budget
+    fn memory_size(&self, _record: &Self::Record) -> usize {
+        std::mem::size_of::<Self::Record>()
+    }
+}
+
+/// Reads records of `N` bytes. The records compare like their bytes, so big endian numbers keep their order.
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub struct FixedSizeCodec<const N: usize>;
+
+impl<const N: usize> RecordCodec for FixedSizeCodec<N> {
+    type Record = [u8; N];
+
+    fn encode<W: Write>(&self, record: &[u8; N], out: &mut W) -> io::Result<()> {
+        out.write_all(record)
+    }
+
+    fn decode<R: Read>(&self, input: &mut R) -> io::Result<Option<[u8; N]>> {
+        let mut record = [0; N];
+        let mut filled = 0;
+        while filled < N {
+            match input.read(&mut record[filled..]) {
+                Ok(0) if filled == 0 => return Ok(None),
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "the input ended in the middle of a record",
+                    ))
+                }
+                Ok(read) => filled += read,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(Some(record))
+    }
+}
+
+/// Controls how much memory and which files an external sort uses
+#[derive(PartialEq, Debug, Clone)]
+pub struct ExternalSortConfig {
+    /// The number of bytes the sort uses for records, as counted by the codec, scratch space and buffers.
+    /// While chunks are sorted, it is shared by the records of a chunk, the scratch space of a stable sort
+    /// and the buffers of the input and the run. While merging, it is shared by the buffers of the runs and
+    /// the output and the records waiting in the merge heap. At least one record is always read.
+    pub memory_budget: usize,
+    /// Keeps equal records in their input order if set, which sorts the chunks with merge sort instead of quicksort
+    pub stable: bool,
+    /// The maximum number of runs that are merged at once. More runs are merged in several passes.
+    /// Fewer runs are merged at once if the memory budget cannot give each of them a buffer of a few KiB.
+    /// Has to be at least 2.
+    pub merge_width: usize,
+    /// The directory the runs are spilled to
+    pub temp_dir: PathBuf,
+}
+
+impl Default for ExternalSortConfig {
+    fn default() -> Self {
+        ExternalSortConfig {
+            memory_budget: DEFAULT_MEMORY_BUDGET,
+            stable: false,
+            merge_width: DEFAULT_MERGE_WIDTH,
+            temp_dir: std::env::temp_dir(),
+        }
+    }
+}
+
+/// Sorts the records read from `input` into `output`, using about `config.memory_budget` bytes of memory.
+/// Chunks that fit the budget are sorted in memory and spilled to temporary files as sorted runs, which are
+/// merged into `output` afterwards. Returns the number of runs that were spilled, 0 if the input fit into
+/// memory. The temporary files are created as new files that only the current user can access and are
+/// removed, also if an error occurs. Returns an `InvalidInput` error if `config.merge_width` is below 2.
+pub fn external_sort<C, R, W>(
+    input: R,
+    output: W,
+    codec: &C,
+    config: &ExternalSortConfig,
+) -> io::Result<usize>
+where
+    C: RecordCodec,
+    C::Record: PartialOrd,
+    R: Read,
+    W: Write,
+{
+    external_sort_by(input, output, codec, config, |a, b| {
+        a.partial_cmp(b).unwrap_or(Ordering::Equal)
+    })
+}
+
+/// Like `external_sort`, but with a comparator function
+pub fn external_sort_by<C, R, W, F>(
+    input: R,
+    output: W,
+    codec: &C,
+    config: &ExternalSortConfig,
+    mut compare: F,
+) -> io::Result<usize>
+where
+    C: RecordCodec,
+    R: Read,
+    W: Write,
+    F: FnMut(&C::Record, &C::Record) -> Ordering,
+{
+    if config.merge_width < 2 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "at least two runs have to be merged at once",
+        ));
+    }
+    let chunk_plan = ChunkPlan::new::<C::Record>(config.memory_budget, config.stable);
+    let plan = MergePlan::new::<C::Record>(config.memory_budget, config.merge_width);
+    let mut input = BufReader::with_capacity(chunk_plan.buffer, input);
+    let mut runs = Vec::new();
+
+    loop {
+        let (mut chunk, finished) = read_chunk(&mut input, codec, &chunk_plan)?;
+        if config.stable {
+            mergesort_by(&mut chunk, &mut compare);
+        } else {
+            quicksort_by(&mut chunk, &mut compare);
+        }
+
+        if finished && runs.is_empty() {
+            // everything fit into memory, so nothing has to be spilled
+            let mut output = BufWriter::with_capacity(chunk_plan.buffer, output);
+            write_records(&mut output, codec, &chunk)?;
+            output.flush()?;
+            return Ok(0);
+        }
+        if !chunk.is_empty() {
+            let run = RunFile::create(&config.temp_dir)?;
+            let mut writer = BufWriter::with_capacity(chunk_plan.buffer, &run.file);
+            write_records(&mut writer, codec, &chunk)?;
+            writer.flush()?;
+            drop(writer);
+            runs.push(run);
+        }
+        if finished {
+            break;
+        }
+    }
+    let spilled = runs.len();
+    // the input was read completely, its buffer would only take memory from the merge
+    drop(input);
+
+    // merge groups of runs into longer runs until one pass can merge all of them. The groups are merged
+    // in order, so records of earlier runs stay in front of equal records of later runs.
+    while runs.len() > plan.width {
+        let mut merged = Vec::new();
+        for group in runs.chunks(plan.width) {
+            let run = RunFile::create(&config.temp_dir)?;
+            let mut writer = BufWriter::with_capacity(plan.buffer, &run.file);
+            merge_runs(group, &mut writer, codec, &mut compare, plan.buffer)?;
+            writer.flush()?;
+            drop(writer);
+            merged.push(run);
+        }
+        runs = merged;
+    }
+    let mut output = BufWriter::with_capacity(plan.buffer, output);
+    merge_runs(&runs, &mut output, codec, &mut compare, plan.buffer)?;
+    output.flush()?;
+    Ok(spilled)
+}
+
+/// How much of a chunk the records and the sort may take and how large the buffers for reading the input
+/// and writing the run are
+#[derive(PartialEq, Debug, Clone, Copy)]
+struct ChunkPlan {
+    /// the bytes the records of a chunk and the scratch space for sorting them may take
+    limit: usize,
+    /// the bytes of scratch space a stable sort needs per record
+    scratch: usize,
+    buffer: usize,
+}
+
+impl ChunkPlan {
+    /// Gives the buffers of the input and the run a sixteenth of the memory budget each, but at most
+    /// `MAX_CHUNK_BUFFER` bytes, and leaves the rest to the records. Merge sort needs scratch space for
+    /// half of the records, so a stable sort counts half a record of scratch for every record it reads.
+    fn new<R>(memory_budget: usize, stable: bool) -> ChunkPlan {
+        let buffer = (memory_budget / 16).clamp(1, MAX_CHUNK_BUFFER);
+        let scratch = if stable {
+            std::mem::size_of::<R>().div_ceil(2)
+        } else {
+            0
+        };
+        ChunkPlan {
+            limit: memory_budget.saturating_sub(2 * buffer),
+            scratch,
+            buffer,
+        }
+    }
+}
+
+/// How many runs are merged at once and how large the buffers for reading and writing them are
+#[derive(PartialEq, Debug, Clone, Copy)]
+struct MergePlan {
+    width: usize,
+    buffer: usize,
+}
+
+impl MergePlan {
+    /// Splits the memory budget between the buffers of `width` runs and the output and the `width`
+    /// records in the merge heap. The width is reduced until every buffer gets at least
+    /// `MIN_RUN_BUFFER` bytes, but at least two runs are merged at once.
+    fn new<R>(memory_budget: usize, merge_width: usize) -> MergePlan {
+        // a record in the heap is stored together with the index of its run
+        let entry_size = std::mem::size_of::<(R, usize)>();
+        let width = (memory_budget.saturating_sub(MIN_RUN_BUFFER) / (MIN_RUN_BUFFER + entry_size))
+            .clamp(2, merge_width);
+        let buffer = (memory_budget.saturating_sub(width * entry_size) / (width + 1)).max(1);
+        MergePlan { width, buffer }
+    }
+}
+
+/// Reads records until the limit of the plan is used up, so a chunk may take one record more than it.
+/// Returns them and whether the input ended.
+fn read_chunk<C: RecordCodec, R: Read>(
+    input: &mut R,
+    codec: &C,
+    plan: &ChunkPlan,
+) -> io::Result<(Vec<C::Record>, bool)> {
+    let mut chunk = Vec::new();
+    let mut used = 0;
+    while used < plan.limit || chunk.is_empty() {
+        match codec.decode(input)? {
+            Some(record) => {
+                used += codec.memory_size(&record) + plan.scratch;
+                chunk.push(record);
+            }
+            None => return Ok((chunk, true)),
+        }
+    }
+    Ok((chunk, false))
+}
+
+fn write_records<C: RecordCodec, W: Write>(
+    out: &mut W,
+    codec: &C,
+    records: &[C::Record],
+) -> io::Result<()> {
+    records
+        .iter()
+        .try_for_each(|record| codec.encode(record, out))
+}
+
+/// Merges the sorted runs into `out`, keeping one record of every run in a heap
+fn merge_runs<C, W, F>(
+    runs: &[RunFile],
+    out: &mut W,
+    codec: &C,
+    compare: &mut F,
+    buffer: usize,
+) -> io::Result<()>
+where
+    C: RecordCodec,
+    W: Write,
+    F: FnMut(&C::Record, &C::Record) -> Ordering,
+{
+    let mut readers = runs
+        .iter()
+        .map(|run| {
+            let mut file = &run.file;
+            file.seek(SeekFrom::Start(0))?;
+            Ok(BufReader::with_capacity(buffer, file))
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+
+    // the heap returns the greatest element first, so the order is reversed. Equal records are taken from
+    // the earlier run first.
+    let mut heap =
+        BinaryHeap::with_comparator(
+            |a: &(C::Record, usize), b: &(C::Record, usize)| match compare(&b.0, &a.0) {
+                Ordering::Equal => b.1.cmp(&a.1),
+                ordering => ordering,
+            },
+        );
+    for (run, reader) in readers.iter_mut().enumerate() {
+        if let Some(record) = codec.decode(reader)? {
+            heap.push((record, run));
+        }
+    }
+    while let Some((record, run)) = heap.pop() {
+        codec.encode(&record, out)?;
+        if let Some(next) = codec.decode(&mut readers[run])? {
+            heap.push((next, run));
+        }
+    }
+    Ok(())
+}
+
+/// A temporary file holding a sorted run, which is removed when it is dropped. The file is kept open
+/// from creating it until it is removed, so its path is never opened a second time.
+struct RunFile {
+    path: PathBuf,
+    file: File,
+}
+
+impl RunFile {
+    /// Creates a new file in `dir`. Existing files and symbolic links are never opened, their name is
+    /// skipped instead.
+    fn create(dir: &Path) -> io::Result<RunFile> {
+        let mut options = OpenOptions::new();
+        options.read(true).write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        loop {
+            let id = NEXT_RUN_ID.fetch_add(1, AtomicOrdering::Relaxed);
+            let path = dir.join(format!("external-sort-{}-{}.run", std::process::id(), id));
+            match options.open(&path) {
+                Ok(file) => return Ok(RunFile { path, file }),
+                Err(error) if error.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(error) => return Err(error),
+            }
+        }
+    }
+}
+
+impl Drop for RunFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+
+    /// A directory of its own for every test, so the tests can check that all runs are removed
+    struct TestDir(PathBuf);
+
+    impl TestDir {
+        fn new(name: &str) -> TestDir {
+            let path = std::env::temp_dir().join(format!(
+                "all-the-algorithms-{}-{}",
+                name,
+                std::process::id()
+            ));
+            fs::create_dir_all(&path).unwrap();
+            TestDir(path)
+        }
+
+        fn is_empty(&self) -> bool {
+            fs::read_dir(&self.0).unwrap().next().is_none()
+        }
+    }
+
+    impl Drop for TestDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn config(dir: &TestDir, memory_budget: usize) -> ExternalSortConfig {
+        ExternalSortConfig {
+            memory_budget,
+            temp_dir: dir.0.clone(),
+            ..ExternalSortConfig::default()
+        }
+    }
+
+    fn encode_numbers(numbers: &[u64]) -> Vec<u8> {
+        numbers.iter().flat_map(|n| n.to_be_bytes()).collect()
+    }
+
+    fn decode_numbers(bytes: &[u8]) -> Vec<u64> {
+        bytes
+            .chunks(8)
+            .map(|chunk| u64::from_be_bytes(chunk.try_into().unwrap()))
+            .collect()
+    }
+
+    /// The number of runs an unstable sort of `count` numbers spills with the memory budget
+    fn expected_runs(count: usize, memory_budget: usize) -> usize {
+        let plan = ChunkPlan::new::<[u8; 8]>(memory_budget, false);
+        count.div_ceil(plan.limit.div_ceil(8))
+    }
+
+    #[test]
+    fn test_external_sort_spills_and_merges() {
+        let dir = TestDir::new("spill");
+        let mut generator = rand::thread_rng();
+        let numbers: Vec<u64> = (0..10_000).map(|_| generator.gen()).collect();
+        let mut output = Vec::new();
+
+        let runs = external_sort(
+            encode_numbers(&numbers).as_slice(),
+            &mut output,
+            &FixedSizeCodec::<8>,
+            &config(&dir, 8 * 500),
+        )
+        .unwrap();
+        assert_eq!(runs, expected_runs(10_000, 8 * 500));
+        assert!(runs > 20);
+
+        let mut expected = numbers;
+        expected.sort();
+        assert_eq!(decode_numbers(&output), expected);
+        assert!(dir.is_empty());
+    }
+
+    #[test]
+    fn test_external_sort_merges_in_several_passes() {
+        let dir = TestDir::new("passes");
+        let numbers: Vec<u64> = (0..5000).map(|i| (i * 7919) % 5000).collect();
+        let mut output = Vec::new();
+        let config = ExternalSortConfig {
+            merge_width: 3,
+            ..config(&dir, 8 * 100)
+        };
+
+        let runs = external_sort(
+            encode_numbers(&numbers).as_slice(),
+            &mut output,
+            &FixedSizeCodec::<8>,
+            &config,
+        )
+        .unwrap();
+        assert_eq!(runs, expected_runs(5000, 8 * 100));
+        assert_eq!(decode_numbers(&output), (0..5000).collect::<Vec<_>>());
+        assert!(dir.is_empty());
+    }
+
+    #[test]
+    fn test_external_sort_in_memory() {
+        let dir = TestDir::new("memory");
+        let mut output = Vec::new();
+        let runs = external_sort(
+            encode_numbers(&[3, 1, 2]).as_slice(),
+            &mut output,
+            &FixedSizeCodec::<8>,
+            &config(&dir, 1 << 20),
+        )
+        .unwrap();
+        assert_eq!(runs, 0);
+        assert_eq!(decode_numbers(&output), [1, 2, 3]);
+
+        let mut output = Vec::new();
+        let runs = external_sort(
+            io::empty(),
+            &mut output,
+            &FixedSizeCodec::<8>,
+            &config(&dir, 0),
+        )
+        .unwrap();
+        assert_eq!(runs, 0);
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_stable_external_sort() {
+        let dir = TestDir::new("stable");
+        // records are a key byte followed by their position in the input
+        let records: Vec<[u8; 3]> = (0..3000u16)
+            .map(|i| {
+                let [high, low] = i.to_be_bytes();
+                [(i % 7 * 3 % 7) as u8, high, low]
+            })
+            .collect();
+        let input: Vec<u8> = records.iter().flatten().copied().collect();
+        let mut output = Vec::new();
+        let config = ExternalSortConfig {
+            stable: true,
+            merge_width: 4,
+            ..config(&dir, 3 * 128)
+        };
+
+        external_sort_by(
+            input.as_slice(),
+            &mut output,
+            &FixedSizeCodec::<3>,
+            &config,
+            |a, b| a[0].cmp(&b[0]),
+        )
+        .unwrap();
+
+        let mut expected = records;
+        expected.sort_by_key(|record| record[0]);
+        let sorted: Vec<[u8; 3]> = output
+            .chunks(3)
+            .map(|chunk| chunk.try_into().unwrap())
+            .collect();
+        assert_eq!(sorted, expected);
+        assert!(dir.is_empty());
+    }
+
+    /// Sorts 5000 numbers with a budget of 100 numbers, so more than 50 runs are spilled into the directory
+    fn sort_with_spilled_runs(dir: &TestDir) {
+        let numbers: Vec<u64> = (0..5000).rev().collect();
+        let mut output = Vec::new();
+        let runs = external_sort(
+            encode_numbers(&numbers).as_slice(),
+            &mut output,
+            &FixedSizeCodec::<8>,
+            &config(dir, 8 * 100),
+        )
+        .unwrap();
+        assert_eq!(runs, expected_runs(5000, 8 * 100));
+        assert_eq!(decode_numbers(&output), (0..5000).collect::<Vec<_>>());
+    }
+
+    /// The names the next run files of this process would get if no other sort took them first
+    fn next_run_names(count: usize) -> Vec<String> {
+        let first = NEXT_RUN_ID.load(AtomicOrdering::Relaxed);
+        (first..first + count)
+            .map(|id| format!("external-sort-{}-{}.run", std::process::id(), id))
+            .collect()
+    }
+
+    #[test]
+    fn test_external_sort_skips_existing_files() {
+        let dir = TestDir::new("existing");
+        let names = next_run_names(1000);
+        for name in &names {
+            fs::write(dir.0.join(name), b"not a run").unwrap();
+        }
+
+        sort_with_spilled_runs(&dir);
+        for name in &names {
+            assert_eq!(fs::read(dir.0.join(name)).unwrap(), b"not a run");
+        }
+        assert_eq!(fs::read_dir(&dir.0).unwrap().count(), names.len());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_external_sort_does_not_follow_symbolic_links() {
+        let dir = TestDir::new("symlinks");
+        let target = TestDir::new("symlink-target");
+        let target_file = target.0.join("victim");
+        fs::write(&target_file, b"keep me").unwrap();
+        let names = next_run_names(1000);
+        for name in &names {
+            std::os::unix::fs::symlink(&target_file, dir.0.join(name)).unwrap();
+        }
+
+        sort_with_spilled_runs(&dir);
+        assert_eq!(fs::read(&target_file).unwrap(), b"keep me");
+        assert_eq!(fs::read_dir(&dir.0).unwrap().count(), names.len());
+    }
+
+    #[test]
+    fn test_merge_plan_fits_the_memory_budget() {
+        let entry_size = std::mem::size_of::<([u8; 8], usize)>();
+        for (budget, merge_width) in [(64 << 20, 64), (64 << 10, 64), (1 << 20, 1000), (800, 64)] {
+            let plan = MergePlan::new::<[u8; 8]>(budget, merge_width);
+            assert!(plan.width >= 2 && plan.width <= merge_width);
+            assert!((plan.width + 1) * plan.buffer + plan.width * entry_size <= budget);
+        }
+        assert_eq!(MergePlan::new::<[u8; 8]>(64 << 20, 64).width, 64);
+        // 64 KiB only leave room for the buffers of 14 runs and the output
+        let plan = MergePlan::new::<[u8; 8]>(64 << 10, 64);
+        assert_eq!(plan.width, 14);
+        assert!(plan.buffer >= MIN_RUN_BUFFER);
+    }
+
+    #[test]
+    fn test_chunk_plan_fits_the_memory_budget() {
+        let numbers: Vec<u64> = (0..100_000).collect();
+        let input = encode_numbers(&numbers);
+        for budget in [64 << 20, 64 << 10, 8 * 500, 800, 16] {
+            for stable in [false, true] {
+                let plan = ChunkPlan::new::<[u8; 8]>(budget, stable);
+                let (chunk, _) =
+                    read_chunk(&mut input.as_slice(), &FixedSizeCodec::<8>, &plan).unwrap();
+                // merge sort allocates scratch space for half of the records
+                let scratch = if stable { chunk.len() / 2 * 8 } else { 0 };
+                let used = chunk.len() * 8 + scratch + 2 * plan.buffer;
+                // the last record read may go over the limit
+                assert!(used <= budget + 8 + plan.scratch, "{} {}", budget, stable);
+                assert!(!chunk.is_empty());
+            }
+        }
+        assert_eq!(
+            ChunkPlan::new::<[u8; 8]>(64 << 20, false).buffer,
+            MAX_CHUNK_BUFFER
+        );
+        // a stable sort fits two thirds of the records of an unstable one
+        let unstable = ChunkPlan::new::<[u8; 8]>(3072, false);
+        let stable = ChunkPlan::new::<[u8; 8]>(3072, true);
+        assert_eq!(
+            stable.limit.div_ceil(8 + stable.scratch) * 3,
+            unstable.limit.div_ceil(8) * 2
+        );
+    }
+
+    #[test]
+    fn test_external_sort_rejects_a_merge_width_below_two() {
+        let dir = TestDir::new("width");
+        let config = ExternalSortConfig {
+            merge_width: 1,
+            ..config(&dir, 1 << 20)
+        };
+        let result = external_sort(
+            encode_numbers(&[2, 1]).as_slice(),
+            io::sink(),
+            &FixedSizeCodec::<8>,
+            &config,
+        );
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert!(dir.is_empty());
+    }
+
+    #[test]
+    fn test_external_sort_rejects_truncated_records() {
+        let dir = TestDir::new("truncated");
+        let mut input = encode_numbers(&[5, 4, 3, 2, 1]);
+        input.pop();
+        let result = external_sort(
+            input.as_slice(),
+            io::sink(),
+            &FixedSizeCodec::<8>,
+            &config(&dir, 16),
+        );
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        assert!(dir.is_empty());
+    }
+}
@@ -0,0 +1,85 @@
+use std::cmp::Ordering;
+
+use super::instrumented::{NoProbe, SortProbe};
+use crate::collections::sift_down;
+
+/// Sorts the elements in place by building a max heap and repeatedly moving its root to the end.
+/// Takes O(n log n) time in every case, but equal elements may be reordered.
+pub fn heapsort<T: PartialOrd>(elements: &mut [T]) {
+    heapsort_impl(elements, &mut |a: &T, b: &T| a < b, &mut NoProbe);
+}
+
+/// Sorts the elements with heapsort and a comparator function
+pub fn heapsort_by<T, F: FnMut(&T, &T) -> Ordering>(elements: &mut [T], mut compare: F) {
+    heapsort_impl(
+        elements,
+        &mut |a: &T, b: &T| compare(a, b) == Ordering::Less,
+        &mut NoProbe,
+    );
+}
+
+/// Sorts the elements with heapsort by the key extracted with `key`
+pub fn heapsort_by_key<T, K: PartialOrd, F: FnMut(&T) -> K>(elements: &mut [T], mut key: F) {
+    heapsort_impl(elements, &mut |a: &T, b: &T| key(a) < key(b), &mut NoProbe);
+}
+
+pub(crate) fn heapsort_impl<T, F: FnMut(&T, &T) -> bool, P: SortProbe>(
+    elements: &mut [T],
+    is_less: &mut F,
+    probe: &mut P,
+) {
+    let swap = &mut |elements: &mut [T], a, b| {
+        elements.swap(a, b);
+        probe.swapped(elements, a, b);
+    };
+    let len = elements.len();
+    for node in (0..len / 2).rev() {
+        sift_down(elements, node, is_less, swap);
+    }
+    for end in (1..len).rev() {
+        swap(elements, 0, end);
+        sift_down(&mut elements[..end], 0, is_less, swap);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sorting::test_helpers::{self, test_panic_safety, test_unstable_sort};
+
+    use super::{heapsort, heapsort_by, heapsort_by_key};
+
+    test_helpers::basic_sorting_tests!(heapsort);
+
+    #[test]
+    fn test_heapsort_with_random_items() {
+        test_unstable_sort(&heapsort, 2000, -1000, 1000);
+    }
+
+    #[test]
+    fn test_heapsort_sorts_completely() {
+        let mut items: Vec<i32> = (0..500).map(|i| (i * 7919) % 500).collect();
+        heapsort(&mut items);
+        assert_eq!(items, (0..500).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_heapsort_by_and_by_key() {
+        let mut items = [3, 1, 4, 1, 5, 9, 2, 6];
+        heapsort_by(&mut items, |a, b| b.cmp(a));
+        assert_eq!(items, [9, 6, 5, 4, 3, 2, 1, 1]);
+
+        let mut items = ["ccc", "a", "bb", ""];
+        heapsort_by_key(&mut items, |item| item.len());
+        assert_eq!(items, ["", "a", "bb", "ccc"]);
+    }
+
+    #[test]
+    fn test_heapsort_is_panic_safe() {
+        test_panic_safety(heapsort, 40);
+    }
+
+    #[test]
+    fn test_heapsort_conformance() {
+        test_helpers::test_sort_conformance(heapsort::<i32>);
+    }
+}
@@ -0,0 +1,164 @@
+use std::cmp::Ordering;
+
+use super::instrumented::{NoProbe, SortProbe};
+
+/// Sorts the elements stabelly by moving each one to the left until it is no longer smaller than its
+/// neighbour. Quadratic, but fast for short or nearly sorted slices.
+pub fn insertionsort<T: PartialOrd>(elements: &mut [T]) {
+    insertionsort_impl(elements, &mut |a: &T, b: &T| a < b, &mut NoProbe);
+}
+
+/// Like `insertionsort`, but with a comparator function
+pub fn insertionsort_by<T, F: FnMut(&T, &T) -> Ordering>(elements: &mut [T], mut compare: F) {
+    insertionsort_impl(
+        elements,
+        &mut |a: &T, b: &T| compare(a, b) == Ordering::Less,
+        &mut NoProbe,
+    );
+}
+
+/// Sorts the elements stabelly by the key extracted with `key`
+pub fn insertionsort_by_key<T, K: PartialOrd, F: FnMut(&T) -> K>(elements: &mut [T], mut key: F) {
+    insertionsort_impl(elements, &mut |a: &T, b: &T| key(a) < key(b), &mut NoProbe);
+}
+
+/// Sorts the elements stabelly like `insertionsort`, but finds the place of each element with a binary
+/// search. Makes O(n log n) comparisons, which pays off when comparing is expensive, but still moves
+/// O(n²) elements.
+pub fn binary_insertionsort<T: PartialOrd>(elements: &mut [T]) {
+    binary_insertionsort_impl(elements, 0, &mut |a: &T, b: &T| a < b, &mut NoProbe);
+}
+
+/// Like `binary_insertionsort`, but with a comparator function
+pub fn binary_insertionsort_by<T, F: FnMut(&T, &T) -> Ordering>(
+    elements: &mut [T],
+    mut compare: F,
+) {
+    binary_insertionsort_impl(
+        elements,
+        0,
+        &mut |a: &T, b: &T| compare(a, b) == Ordering::Less,
+        &mut NoProbe,
+    );
+}
+
+/// Sorts the elements stabelly like `binary_insertionsort` by the key extracted with `key`
+pub fn binary_insertionsort_by_key<T, K: PartialOrd, F: FnMut(&T) -> K>(
+    elements: &mut [T],
+    mut key: F,
+) {
+    binary_insertionsort_impl(
+        elements,
+        0,
+        &mut |a: &T, b: &T| key(a) < key(b),
+        &mut NoProbe,
+    );
+}
+
+pub(crate) fn insertionsort_impl<T, F: FnMut(&T, &T) -> bool, P: SortProbe>(
+    elements: &mut [T],
+    is_less: &mut F,
+    probe: &mut P,
+) {
+    for i in 1..elements.len() {
+        let mut j = i;
+        while j > 0 && is_less(&elements[j], &elements[j - 1]) {
+            elements.swap(j, j - 1);
+            probe.swapped(elements, j, j - 1);
+            j -= 1;
+        }
+    }
+}
+
+/// Sorts the elements stabelly, given that the first `sorted` of them are already sorted.
+/// Every following element is inserted behind the last element that is not greater than it.
+pub(crate) fn binary_insertionsort_impl<T, F: FnMut(&T, &T) -> bool, P: SortProbe>(
+    elements: &mut [T],
+    sorted: usize,
+    is_less: &mut F,
+    probe: &mut P,
+) {
+    for i in usize::max(sorted, 1)..elements.len() {
+        let (sorted_part, rest) = elements.split_at(i);
+        let key = &rest[0];
+        let position = sorted_part.partition_point(|element| !is_less(key, element));
+        if position < i {
+            elements[position..=i].rotate_right(1);
+            probe.wrote(i - position + 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sorting::test_helpers::{
+        self, is_stabelly_sorted, random_comparable_list, test_panic_safety,
+    };
+
+    use super::*;
+
+    test_helpers::basic_sorting_tests!(insertionsort);
+
+    mod binary {
+        use super::super::binary_insertionsort;
+        use crate::sorting::test_helpers;
+
+        test_helpers::basic_sorting_tests!(binary_insertionsort);
+    }
+
+    #[test]
+    fn test_insertionsort_is_stable() {
+        let mut data = random_comparable_list(100, 0, 10);
+        insertionsort(&mut data);
+        assert!(is_stabelly_sorted(&data));
+    }
+
+    #[test]
+    fn test_insertionsort_is_panic_safe() {
+        test_panic_safety(insertionsort, 30);
+        test_panic_safety(binary_insertionsort, 30);
+    }
+
+    #[test]
+    fn test_by_and_by_key() {
+        let mut data = [3, 1, 4, 1, 5, 9, 2, 6];
+        insertionsort_by(&mut data, |a, b| b.cmp(a));
+        assert_eq!(data, [9, 6, 5, 4, 3, 2, 1, 1]);
+        binary_insertionsort_by(&mut data, |a, b| a.cmp(b));
+        assert_eq!(data, [1, 1, 2, 3, 4, 5, 6, 9]);
+
+        let mut data: Vec<(i32, usize)> = [2, 0, 1, 0, 2, 1].iter().cloned().zip(0..).collect();
+        let expected = [(0, 1), (0, 3), (1, 2), (1, 5), (2, 0), (2, 4)];
+        let mut binary = data.clone();
+        insertionsort_by_key(&mut data, |item| item.0);
+        assert_eq!(data, expected);
+        binary_insertionsort_by_key(&mut binary, |item| item.0);
+        assert_eq!(binary, expected);
+    }
+
+    #[test]
+    fn test_binary_insertionsort_comparisons() {
+        let mut data: Vec<u32> = (0..256).rev().collect();
+        let mut comparisons = 0u32;
+        binary_insertionsort_by(&mut data, |a, b| {
+            comparisons += 1;
+            a.cmp(b)
+        });
+        assert!(data.iter().copied().eq(0..256));
+        // the binary search over the first i elements takes at most log2(i) + 2 comparisons
+        assert!(comparisons <= (1..256u32).map(|i| i.ilog2() + 2).sum());
+    }
+
+    #[test]
+    fn test_binary_insertionsort_with_sorted_prefix() {
+        let mut data = [1, 4, 7, 3, 0, 7, 2];
+        binary_insertionsort_impl(&mut data, 3, &mut |a: &i32, b: &i32| a < b, &mut NoProbe);
+        assert_eq!(data, [0, 1, 2, 3, 4, 7, 7]);
+    }
+
+    #[test]
+    fn test_insertionsort_conformance() {
+        test_helpers::test_stable_sort_conformance(insertionsort);
+        test_helpers::test_stable_sort_conformance(binary_insertionsort);
+    }
+}
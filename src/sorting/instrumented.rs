@@ -0,0 +1,437 @@
+use std::cell::Cell;
+use std::cmp::Ordering;
+
+use super::bubblesort::bubblesort_impl;
+use super::heapsort::heapsort_impl;
+use super::insertionsort::{binary_insertionsort_impl, insertionsort_impl};
+use super::mergesort::mergesort_impl;
+use super::pivot::MedianOfThree;
+use super::quicksort::quicksort_impl;
+use super::shellsort::{shellsort_impl, GapSequence};
+
+/// What a sort did while sorting a slice
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub struct SortStats {
+    /// How often two elements were compared
+    pub comparisons: usize,
+    /// How often two elements were swapped
+    pub swaps: usize,
+    /// How often an element was written to the slice or to scratch memory. A swap counts as two writes.
+    pub writes: usize,
+    /// The deepest nesting of recursive calls, 0 for sorts that do not recurse
+    pub max_recursion_depth: usize,
+    /// The number of bytes of scratch memory allocated for the elements
+    pub scratch_bytes: usize,
+}
+
+/// Counts the comparisons done by any comparator function it wraps, so the comparisons of every `*_by` sort
+/// can be counted without changing the sort.
+#[derive(Debug, Default)]
+pub struct ComparisonCounter {
+    comparisons: Cell<usize>,
+}
+
+impl ComparisonCounter {
+    pub fn new() -> Self {
+        ComparisonCounter::default()
+    }
+
+    /// Returns a comparator that behaves like `compare` and counts every call
+    pub fn wrap<'a, T, F: FnMut(&T, &T) -> Ordering + 'a>(
+        &'a self,
+        mut compare: F,
+    ) -> impl FnMut(&T, &T) -> Ordering + 'a {
+        move |a: &T, b: &T| {
+            self.comparisons.set(self.comparisons.get() + 1);
+            compare(a, b)
+        }
+    }
+
+    /// The number of comparisons counted so far
+    pub fn comparisons(&self) -> usize {
+        self.comparisons.get()
+    }
+
+    pub fn reset(&self) {
+        self.comparisons.set(0);
+    }
+}
+
+/// Gets told about the work a sort does besides comparing. Every method does nothing by default, so the sorts
+/// that are not instrumented compile to the same code as before.
+/// Indexes are relative to the slice that is passed along, which may be a part of the sorted slice.
+pub(crate) trait SortProbe {
+    /// The elements at `a` and `b` of `elements` were swapped
+    fn swapped<T>(&mut self, _elements: &[T], _a: usize, _b: usize) {}
+
+    /// `count` elements were moved without swapping
+    fn wrote(&mut self, _count: usize) {}
+
+    /// A recursive call started
+    fn entered(&mut self) {}
+
+    /// A recursive call finished
+    fn returned(&mut self) {}
+
+    /// Scratch memory of `bytes` bytes was allocated
+    fn allocated(&mut self, _bytes: usize) {}
+
+    /// `elements` are about to be partitioned
+    fn partitioning<T>(&mut self, _elements: &[T]) {}
+
+    /// The element at `pivot` was chosen to partition `elements` around
+    fn pivot_chosen<T>(&mut self, _elements: &[T], _pivot: usize) {}
+
+    /// `elements` were partitioned and the elements equal to the pivot ended up in `start..end`
+    fn partitioned<T>(&mut self, _elements: &[T], _start: usize, _end: usize) {}
+}
+
+/// The probe of the sorts that are not instrumented
+pub(crate) struct NoProbe;
+
+impl SortProbe for NoProbe {}
+
+#[derive(Default)]
+struct StatsProbe {
+    stats: SortStats,
+    depth: usize,
+}
+
+impl SortProbe for StatsProbe {
+    fn swapped<T>(&mut self, _elements: &[T], _a: usize, _b: usize) {
+        self.stats.swaps += 1;
+        self.stats.writes += 2;
+    }
+
+    fn wrote(&mut self, count: usize) {
+        self.stats.writes += count;
+    }
+
+    fn entered(&mut self) {
+        self.depth += 1;
+        self.stats.max_recursion_depth = usize::max(self.stats.max_recursion_depth, self.depth);
+    }
+
+    fn returned(&mut self) {
+        self.depth -= 1;
+    }
+
+    fn allocated(&mut self, bytes: usize) {
+        self.stats.scratch_bytes += bytes;
+    }
+}
+
+impl StatsProbe {
+    fn finish(mut self, comparisons: usize) -> SortStats {
+        self.stats.comparisons = comparisons;
+        self.stats
+    }
+}
+
+fn counting_less<T: PartialOrd>(comparisons: &mut usize) -> impl FnMut(&T, &T) -> bool + '_ {
+    move |a: &T, b: &T| {
+        *comparisons += 1;
+        a < b
+    }
+}
+
+/// Sorts the elements like `bubblesort` and returns what it took
+pub fn bubblesort_instrumented<T: PartialOrd>(elements: &mut [T]) -> SortStats {
+    let mut probe = StatsProbe::default();
+    let mut comparisons = 0;
+    bubblesort_impl(elements, &mut counting_less(&mut comparisons), &mut probe);
+    probe.finish(comparisons)
+}
+
+/// Sorts the elements like `insertionsort` and returns what it took
+pub fn insertionsort_instrumented<T: PartialOrd>(elements: &mut [T]) -> SortStats {
+    let mut probe = StatsProbe::default();
+    let mut comparisons = 0;
+    insertionsort_impl(elements, &mut counting_less(&mut comparisons), &mut probe);
+    probe.finish(comparisons)
+}
+
+/// Sorts the elements like `binary_insertionsort` and returns what it took
+pub fn binary_insertionsort_instrumented<T: PartialOrd>(elements: &mut [T]) -> SortStats {
+    let mut probe = StatsProbe::default();
+    let mut comparisons = 0;
+    binary_insertionsort_impl(
+        elements,
+        0,
+        &mut counting_less(&mut comparisons),
+        &mut probe,
+    );
+    probe.finish(comparisons)
+}
+
+/// Sorts the elements like `shellsort_with` and returns what it took
+pub fn shellsort_instrumented<T: PartialOrd>(elements: &mut [T], gaps: GapSequence) -> SortStats {
+    let mut probe = StatsProbe::default();
+    let mut comparisons = 0;
+    shellsort_impl(
+        elements,
+        &mut counting_less(&mut comparisons),
+        gaps,
+        &mut probe,
+    );
+    probe.finish(comparisons)
+}
+
+/// Sorts the elements like `quicksort` and returns what it took
+pub fn quicksort_instrumented<T: PartialOrd>(elements: &mut [T]) -> SortStats {
+    let mut probe = StatsProbe::default();
+    let mut comparisons = 0;
+    quicksort_impl(
+        elements,
+        &mut counting_less(&mut comparisons),
+        &mut MedianOfThree,
+        &mut probe,
+    );
+    probe.finish(comparisons)
+}
+
+/// Sorts the elements like `mergesort` and returns what it took
+pub fn mergesort_instrumented<T: PartialOrd>(elements: &mut [T]) -> SortStats {
+    let mut probe = StatsProbe::default();
+    let mut comparisons = 0;
+    mergesort_impl(elements, &mut counting_less(&mut comparisons), &mut probe);
+    probe.finish(comparisons)
+}
+
+/// Sorts the elements like `heapsort` and returns what it took
+pub fn heapsort_instrumented<T: PartialOrd>(elements: &mut [T]) -> SortStats {
+    let mut probe = StatsProbe::default();
+    let mut comparisons = 0;
+    heapsort_impl(elements, &mut counting_less(&mut comparisons), &mut probe);
+    probe.finish(comparisons)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::seq::SliceRandom;
+
+    use crate::sorting::quicksort::partition;
+    use crate::sorting::{mergesort_by, quicksort_by};
+
+    use super::*;
+
+    fn shuffled(len: usize) -> Vec<u64> {
+        let mut items: Vec<u64> = (0..len as u64).collect();
+        items.shuffle(&mut rand::thread_rng());
+        items
+    }
+
+    #[test]
+    fn test_bubblesort_stats() {
+        let n = 100;
+        let mut items: Vec<u64> = (0..n as u64).rev().collect();
+        let stats = bubblesort_instrumented(&mut items);
+        assert!(items.iter().copied().eq(0..n as u64));
+        assert_eq!(
+            stats,
+            SortStats {
+                comparisons: n * (n - 1) / 2,
+                swaps: n * (n - 1) / 2,
+                writes: n * (n - 1),
+                max_recursion_depth: 0,
+                scratch_bytes: 0,
+            }
+        );
+
+        let stats = bubblesort_instrumented(&mut items);
+        assert_eq!(stats.comparisons, n - 1);
+        assert_eq!(stats.swaps, 0);
+    }
+
+    #[test]
+    fn test_insertion_sort_stats() {
+        let n = 100;
+        let reversed: Vec<u64> = (0..n as u64).rev().collect();
+        let mut items = reversed.clone();
+        let stats = insertionsort_instrumented(&mut items);
+        assert!(items.iter().copied().eq(0..n as u64));
+        assert_eq!(stats.comparisons, n * (n - 1) / 2);
+        assert_eq!(stats.swaps, n * (n - 1) / 2);
+
+        // every element is rotated to the front
+        let mut items = reversed;
+        let stats = binary_insertionsort_instrumented(&mut items);
+        assert!(items.iter().copied().eq(0..n as u64));
+        assert!(stats.comparisons <= n * (n.ilog2() as usize + 2));
+        assert_eq!(stats.swaps, 0);
+        assert_eq!(stats.writes, (2..=n).sum::<usize>());
+    }
+
+    #[test]
+    fn test_shellsort_stats() {
+        let items = shuffled(2000);
+        let mut sorted = items.clone();
+        let insertion = insertionsort_instrumented(&mut sorted);
+        for gaps in [
+            GapSequence::Shell,
+            GapSequence::Knuth,
+            GapSequence::Sedgewick,
+            GapSequence::Ciura,
+            GapSequence::Tokuda,
+        ] {
+            let mut sorted = items.clone();
+            let stats = shellsort_instrumented(&mut sorted, gaps);
+            assert!(sorted.iter().copied().eq(0..2000));
+            assert!(stats.comparisons < insertion.comparisons / 10);
+            assert_eq!(stats.writes, 2 * stats.swaps);
+            assert_eq!(stats.max_recursion_depth, 0);
+            assert_eq!(stats.scratch_bytes, 0);
+        }
+    }
+
+    #[test]
+    fn test_quicksort_stats() {
+        let n = 10_000;
+        let items = shuffled(n);
+        let counter = ComparisonCounter::new();
+        let mut expected = items.clone();
+        quicksort_by(&mut expected, counter.wrap(u64::cmp));
+
+        let mut sorted = items;
+        let stats = quicksort_instrumented(&mut sorted);
+        assert_eq!(sorted, expected);
+        assert_eq!(stats.comparisons, counter.comparisons());
+        assert!(stats.swaps > 0);
+        assert_eq!(stats.writes, 2 * stats.swaps);
+        // only the smaller side of a partition is recursed into
+        assert!(stats.max_recursion_depth >= 1);
+        assert!(stats.max_recursion_depth <= n.ilog2() as usize + 1);
+        assert_eq!(stats.scratch_bytes, 0);
+    }
+
+    #[test]
+    fn test_mergesort_stats() {
+        let n = 1024;
+        let items = shuffled(n);
+        let counter = ComparisonCounter::new();
+        let mut expected = items.clone();
+        mergesort_by(&mut expected, counter.wrap(u64::cmp));
+
+        let mut sorted = items;
+        let stats = mergesort_instrumented(&mut sorted);
+        assert_eq!(sorted, expected);
+        assert_eq!(stats.comparisons, counter.comparisons());
+        assert_eq!(stats.swaps, 0);
+        assert!(stats.writes > n);
+        assert_eq!(stats.max_recursion_depth, 10);
+        assert_eq!(stats.scratch_bytes, n / 2 * std::mem::size_of::<u64>());
+
+        // every merge of sorted runs is skipped after a single comparison
+        let stats = mergesort_instrumented(&mut sorted);
+        assert_eq!(stats.comparisons, n - 1);
+        assert_eq!(stats.writes, 0);
+    }
+
+    #[test]
+    fn test_mergesort_writes_on_reversed_input() {
+        // every merge copies the left run into the buffer, moves the right run to the front and
+        // writes the left run back behind it
+        let mut items = [4, 3, 2, 1];
+        let stats = mergesort_instrumented(&mut items);
+        assert_eq!(items, [1, 2, 3, 4]);
+        assert_eq!(stats.writes, 3 + 3 + 6);
+    }
+
+    #[test]
+    fn test_heapsort_stats() {
+        let mut items = shuffled(1000);
+        let stats = heapsort_instrumented(&mut items);
+        assert!(items.iter().copied().eq(0..1000));
+        assert!(stats.swaps >= 999);
+        assert_eq!(stats.max_recursion_depth, 0);
+        assert_eq!(stats.scratch_bytes, 0);
+    }
+
+    #[test]
+    fn test_comparison_counter() {
+        let counter = ComparisonCounter::new();
+        let mut items = [3, 1, 2];
+        crate::sorting::bubblesort_by(&mut items, counter.wrap(i32::cmp));
+        assert_eq!(items, [1, 2, 3]);
+        assert_eq!(counter.comparisons(), 3);
+        counter.reset();
+        assert_eq!(counter.comparisons(), 0);
+    }
+
+    #[test]
+    fn test_empty_slices() {
+        let mut items: [u8; 0] = [];
+        assert_eq!(bubblesort_instrumented(&mut items), SortStats::default());
+        assert_eq!(heapsort_instrumented(&mut items), SortStats::default());
+        assert_eq!(quicksort_instrumented(&mut items).comparisons, 0);
+        assert_eq!(mergesort_instrumented(&mut items).comparisons, 0);
+    }
+
+    #[test]
+    fn test_partition_only_counts_swaps_that_move_elements() {
+        // the pivot is the smallest element, so it stays at the front and nothing is moved
+        let mut items = [1, 2, 3, 4];
+        let mut probe = StatsProbe::default();
+        partition(&mut items, 0, &mut |a: &i32, b: &i32| a < b, &mut probe);
+        assert_eq!(items, [1, 2, 3, 4]);
+        assert_eq!(probe.finish(0), SortStats::default());
+
+        // only moving the pivot behind the smaller elements is a swap
+        let mut items = [3, 1, 2];
+        let mut probe = StatsProbe::default();
+        partition(&mut items, 0, &mut |a: &i32, b: &i32| a < b, &mut probe);
+        assert_eq!(items, [2, 1, 3]);
+        let stats = probe.finish(0);
+        assert_eq!((stats.swaps, stats.writes), (1, 2));
+    }
+
+    /// Counts the reported swaps that exchange an element with itself
+    #[derive(Default)]
+    struct SelfSwapProbe {
+        self_swaps: usize,
+    }
+
+    impl SortProbe for SelfSwapProbe {
+        fn swapped<T>(&mut self, _elements: &[T], a: usize, b: usize) {
+            if a == b {
+                self.self_swaps += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn test_partition_does_not_swap_elements_with_themselves() {
+        // the scans stop next to each other on the second 1, which stays. Only the pivot is moved behind it
+        let mut items = [1, 1, 2];
+        let mut probe = StatsProbe::default();
+        partition(&mut items, 0, &mut |a: &i32, b: &i32| a < b, &mut probe);
+        let stats = probe.finish(0);
+        assert_eq!((stats.swaps, stats.writes), (1, 2));
+
+        // presorted elements around the smallest one are not moved at all
+        for len in 1..20 {
+            let mut items: Vec<usize> = (0..len).collect();
+            let mut probe = StatsProbe::default();
+            partition(&mut items, 0, &mut |a: &usize, b: &usize| a < b, &mut probe);
+            assert_eq!(probe.finish(0).swaps, 0);
+        }
+
+        for len in 0..50 {
+            let inputs: [Vec<usize>; 3] = [
+                vec![7; len],
+                (0..len).collect(),
+                (0..len).map(|i| i / 2).collect(),
+            ];
+            for mut items in inputs {
+                let mut probe = SelfSwapProbe::default();
+                quicksort_impl(
+                    &mut items,
+                    &mut |a: &usize, b: &usize| a < b,
+                    &mut MedianOfThree,
+                    &mut probe,
+                );
+                assert_eq!(probe.self_swaps, 0, "length {}", len);
+            }
+        }
+    }
+}
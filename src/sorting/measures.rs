@@ -0,0 +1,328 @@
+use std::cmp::Ordering;
+
+use super::permutation::{invert_permutation, is_permutation};
+
+/// Returns the number of inversions, the pairs of elements that are out of order. This is the number of
+/// swaps `bubblesort` and `insertionsort` need, 0 for a sorted slice and n(n - 1)/2 for a reversed one
+/// without duplicates. Equal elements are no inversion. Counted while merge sorting references to the
+/// elements, which takes O(n log n) time.
+pub fn count_inversions<T: PartialOrd>(elements: &[T]) -> usize {
+    count_inversions_impl(elements, &mut |a: &T, b: &T| a < b)
+}
+
+/// Like `count_inversions`, but with a comparator function
+pub fn count_inversions_by<T, F: FnMut(&T, &T) -> Ordering>(
+    elements: &[T],
+    mut compare: F,
+) -> usize {
+    count_inversions_impl(elements, &mut |a: &T, b: &T| {
+        compare(a, b) == Ordering::Less
+    })
+}
+
+/// Returns the number of ascending runs, the maximal parts of the slice that are sorted already.
+/// A sorted slice has one run, a reversed one without duplicates has one per element. This is about the
+/// number of runs `timsort` has to merge.
+pub fn count_runs<T: PartialOrd>(elements: &[T]) -> usize {
+    count_runs_impl(elements, &mut |a: &T, b: &T| a < b)
+}
+
+/// Like `count_runs`, but with a comparator function
+pub fn count_runs_by<T, F: FnMut(&T, &T) -> Ordering>(elements: &[T], mut compare: F) -> usize {
+    count_runs_impl(elements, &mut |a: &T, b: &T| {
+        compare(a, b) == Ordering::Less
+    })
+}
+
+/// Returns the length of the longest strictly increasing subsequence. The elements outside of it are the
+/// fewest that have to be moved to sort the slice. Uses patience sorting with a binary search per
+/// element, which takes O(n log n) time.
+pub fn longest_increasing_subsequence_len<T: PartialOrd>(elements: &[T]) -> usize {
+    longest_increasing_subsequence_impl(elements, &mut |a: &T, b: &T| a < b)
+}
+
+/// Like `longest_increasing_subsequence_len`, but with a comparator function
+pub fn longest_increasing_subsequence_len_by<T, F: FnMut(&T, &T) -> Ordering>(
+    elements: &[T],
+    mut compare: F,
+) -> usize {
+    longest_increasing_subsequence_impl(elements, &mut |a: &T, b: &T| {
+        compare(a, b) == Ordering::Less
+    })
+}
+
+/// Returns the Kendall tau distance of two permutations: the number of pairs of values that the two put
+/// in a different order. It is the number of adjacent swaps that turn one into the other and is counted
+/// as the inversions of `first` relabeled by the positions in `second`. Takes O(n log n) time.
+///
+/// Panics if the arguments are not permutations of the same length.
+pub fn kendall_tau_distance(first: &[usize], second: &[usize]) -> usize {
+    assert!(
+        first.len() == second.len() && is_permutation(first) && is_permutation(second),
+        "kendall_tau_distance needs two permutations of the same length"
+    );
+    let position_in_second = invert_permutation(second);
+    let relabeled: Vec<usize> = first
+        .iter()
+        .map(|&value| position_in_second[value])
+        .collect();
+    count_inversions(&relabeled)
+}
+
+fn count_inversions_impl<T, F: FnMut(&T, &T) -> bool>(elements: &[T], is_less: &mut F) -> usize {
+    let mut references: Vec<&T> = elements.iter().collect();
+    let mut buffer = Vec::with_capacity(elements.len() / 2);
+    count_inversions_recursive(&mut references, &mut buffer, is_less)
+}
+
+/// Sorts the references stabelly and returns how many inversions there were
+fn count_inversions_recursive<'a, T, F: FnMut(&T, &T) -> bool>(
+    elements: &mut [&'a T],
+    buffer: &mut Vec<&'a T>,
+    is_less: &mut F,
+) -> usize {
+    let len = elements.len();
+    if len <= 1 {
+        return 0;
+    }
+    let mid = len / 2;
+    let mut inversions = count_inversions_recursive(&mut elements[..mid], buffer, is_less)
+        + count_inversions_recursive(&mut elements[mid..], buffer, is_less);
+
+    buffer.clear();
+    buffer.extend_from_slice(&elements[..mid]);
+    let mut left = 0;
+    let mut right = mid;
+    let mut out = 0;
+    while left < buffer.len() && right < len {
+        if is_less(elements[right], buffer[left]) {
+            // the element from the right run is less than every element that is left in the left run
+            inversions += buffer.len() - left;
+            elements[out] = elements[right];
+            right += 1;
+        } else {
+            elements[out] = buffer[left];
+            left += 1;
+        }
+        out += 1;
+    }
+    let rest = &buffer[left..];
+    elements[out..out + rest.len()].copy_from_slice(rest);
+    inversions
+}
+
+fn count_runs_impl<T, F: FnMut(&T, &T) -> bool>(elements: &[T], is_less: &mut F) -> usize {
+    if elements.is_empty() {
+        return 0;
+    }
+    // every descent starts a new run
+    1 + elements
+        .windows(2)
+        .filter(|pair| is_less(&pair[1], &pair[0]))
+        .count()
+}
+
+fn longest_increasing_subsequence_impl<T, F: FnMut(&T, &T) -> bool>(
+    elements: &[T],
+    is_less: &mut F,
+) -> usize {
+    // tails[i] is the smallest element that ends an increasing subsequence of length i + 1. It is sorted,
+    // so every element replaces the first tail that is not less than it.
+    let mut tails: Vec<&T> = Vec::new();
+    for element in elements {
+        let position = tails.partition_point(|tail| is_less(tail, element));
+        if position == tails.len() {
+            tails.push(element);
+        } else {
+            tails[position] = element;
+        }
+    }
+    tails.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::seq::SliceRandom;
+    use rand::Rng;
+
+    use crate::sorting::test_helpers::InputShape;
+    use crate::sorting::{bubblesort_instrumented, insertionsort_instrumented};
+
+    use super::*;
+
+    fn naive_inversions<T: PartialOrd>(elements: &[T]) -> usize {
+        let mut inversions = 0;
+        for i in 0..elements.len() {
+            for j in i + 1..elements.len() {
+                if elements[j] < elements[i] {
+                    inversions += 1;
+                }
+            }
+        }
+        inversions
+    }
+
+    fn naive_longest_increasing_subsequence<T: PartialOrd>(elements: &[T]) -> usize {
+        // longest[i] is the length of the longest increasing subsequence that ends at i
+        let mut longest = vec![1; elements.len()];
+        for i in 0..elements.len() {
+            for j in 0..i {
+                if elements[j] < elements[i] {
+                    longest[i] = usize::max(longest[i], longest[j] + 1);
+                }
+            }
+        }
+        longest.into_iter().max().unwrap_or(0)
+    }
+
+    fn random_permutation(len: usize) -> Vec<usize> {
+        let mut permutation: Vec<usize> = (0..len).collect();
+        permutation.shuffle(&mut rand::thread_rng());
+        permutation
+    }
+
+    #[test]
+    fn test_count_inversions_edge_cases() {
+        assert_eq!(count_inversions::<i32>(&[]), 0);
+        assert_eq!(count_inversions(&[1]), 0);
+        assert_eq!(count_inversions(&[2, 1]), 1);
+        assert_eq!(count_inversions(&[5, 5, 5]), 0);
+        assert_eq!(count_inversions(&[3, 1, 2]), 2);
+        let reversed: Vec<u32> = (0..100).rev().collect();
+        assert_eq!(count_inversions(&reversed), 100 * 99 / 2);
+    }
+
+    #[test]
+    fn test_count_inversions_against_naive() {
+        let mut generator = rand::thread_rng();
+        for len in [2, 10, 100, 1000] {
+            let items: Vec<i32> = (0..len).map(|_| generator.gen_range(-20..20)).collect();
+            assert_eq!(count_inversions(&items), naive_inversions(&items));
+        }
+        let shape = InputShape::MedianOfThreeKiller.generate(500);
+        assert_eq!(count_inversions(&shape), naive_inversions(&shape));
+    }
+
+    #[test]
+    fn test_count_inversions_matches_bubblesort_swaps() {
+        let mut generator = rand::thread_rng();
+        for len in [0, 1, 5, 50, 500] {
+            let items: Vec<i32> = (0..len).map(|_| generator.gen_range(-10..10)).collect();
+            let inversions = count_inversions(&items);
+            assert_eq!(
+                bubblesort_instrumented(&mut items.clone()).swaps,
+                inversions
+            );
+            assert_eq!(
+                insertionsort_instrumented(&mut items.clone()).swaps,
+                inversions
+            );
+        }
+        for shape in InputShape::ALL {
+            let mut items = shape.generate(300);
+            let inversions = count_inversions(&items);
+            assert_eq!(
+                bubblesort_instrumented(&mut items).swaps,
+                inversions,
+                "{:?}",
+                shape
+            );
+        }
+    }
+
+    #[test]
+    fn test_count_inversions_by() {
+        let items = [1, 2, 3, 4];
+        assert_eq!(count_inversions_by(&items, |a, b| b.cmp(a)), 6);
+        let words = ["pear", "fig", "apple"];
+        assert_eq!(count_inversions_by(&words, |a, b| a.len().cmp(&b.len())), 1);
+    }
+
+    #[test]
+    fn test_count_runs() {
+        assert_eq!(count_runs::<i32>(&[]), 0);
+        assert_eq!(count_runs(&[7]), 1);
+        assert_eq!(count_runs(&[1, 2, 2, 3]), 1);
+        assert_eq!(count_runs(&[1, 3, 2, 4, 0]), 3);
+        assert_eq!(count_runs(&InputShape::Reversed.generate(50)), 50);
+        assert_eq!(count_runs(&InputShape::Sawtooth.generate(1000)), 5);
+        assert_eq!(count_runs_by(&[1, 3, 2, 4, 0], |a, b| b.cmp(a)), 3);
+    }
+
+    #[test]
+    fn test_longest_increasing_subsequence() {
+        assert_eq!(longest_increasing_subsequence_len::<i32>(&[]), 0);
+        assert_eq!(longest_increasing_subsequence_len(&[3, 3, 3]), 1);
+        assert_eq!(
+            longest_increasing_subsequence_len(&[10, 9, 2, 5, 3, 7, 101, 18]),
+            4
+        );
+        assert_eq!(
+            longest_increasing_subsequence_len(&InputShape::Presorted.generate(100)),
+            100
+        );
+        assert_eq!(
+            longest_increasing_subsequence_len(&InputShape::Reversed.generate(100)),
+            1
+        );
+        assert_eq!(
+            longest_increasing_subsequence_len_by(&[5, 1, 4, 2, 3], |a, b| b.cmp(a)),
+            3
+        );
+
+        let mut generator = rand::thread_rng();
+        for len in [1, 10, 300] {
+            let items: Vec<i32> = (0..len).map(|_| generator.gen_range(0..50)).collect();
+            assert_eq!(
+                longest_increasing_subsequence_len(&items),
+                naive_longest_increasing_subsequence(&items)
+            );
+        }
+    }
+
+    #[test]
+    fn test_kendall_tau_distance() {
+        let identity: Vec<usize> = (0..10).collect();
+        let reversed: Vec<usize> = (0..10).rev().collect();
+        assert_eq!(kendall_tau_distance(&[], &[]), 0);
+        assert_eq!(kendall_tau_distance(&identity, &identity), 0);
+        assert_eq!(kendall_tau_distance(&identity, &reversed), 45);
+        assert_eq!(kendall_tau_distance(&[0, 1, 2], &[1, 0, 2]), 1);
+
+        for len in [2, 20, 200] {
+            let first = random_permutation(len);
+            let second = random_permutation(len);
+            let mut naive = 0;
+            for a in 0..len {
+                for b in a + 1..len {
+                    let first_order =
+                        first.iter().position(|&v| v == a) < first.iter().position(|&v| v == b);
+                    let second_order =
+                        second.iter().position(|&v| v == a) < second.iter().position(|&v| v == b);
+                    if first_order != second_order {
+                        naive += 1;
+                    }
+                }
+            }
+            assert_eq!(kendall_tau_distance(&first, &second), naive);
+            assert_eq!(kendall_tau_distance(&second, &first), naive);
+            // against the identity it is the number of inversions
+            assert_eq!(
+                kendall_tau_distance(&first, &(0..len).collect::<Vec<_>>()),
+                count_inversions(&first)
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_kendall_tau_distance_rejects_non_permutations() {
+        kendall_tau_distance(&[0, 0, 1], &[0, 1, 2]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_kendall_tau_distance_rejects_different_lengths() {
+        kendall_tau_distance(&[0, 1], &[0, 1, 2]);
+    }
+}
@@ -0,0 +1,358 @@
+use std::cmp::Ordering;
+use std::ptr;
+
+use super::instrumented::{NoProbe, SortProbe};
+
+/// Sorts the elements stabelly with a top down merge sort. Needs a scratch buffer for half of the elements.
+pub fn mergesort<T: PartialOrd>(elements: &mut [T]) {
+    mergesort_impl(elements, &mut |a: &T, b: &T| a < b, &mut NoProbe);
+}
+
+/// Sorts the elements stabelly with a comparator function.
+/// If `compare` panics, the elements are left in some permutation of the input.
+pub fn mergesort_by<T, F: FnMut(&T, &T) -> Ordering>(elements: &mut [T], mut compare: F) {
+    mergesort_impl(
+        elements,
+        &mut |a: &T, b: &T| compare(a, b) == Ordering::Less,
+        &mut NoProbe,
+    );
+}
+
+/// Sorts the elements stabelly by the key extracted with `key`
+pub fn mergesort_by_key<T, K: PartialOrd, F: FnMut(&T) -> K>(elements: &mut [T], mut key: F) {
+    mergesort_impl(elements, &mut |a: &T, b: &T| key(a) < key(b), &mut NoProbe);
+}
+
+/// Like `mergesort`, but reuses the allocation of `buffer` for the scratch space.
+/// The buffer is cleared first and grown if it is too small.
+pub fn mergesort_with_buffer<T: PartialOrd>(elements: &mut [T], buffer: &mut Vec<T>) {
+    buffer.clear();
+    buffer.reserve(elements.len() / 2);
+    mergesort_recursive(elements, buffer, &mut |a: &T, b: &T| a < b, &mut NoProbe);
+}
+
+/// Sorts the elements stabelly by merging runs of width 1, 2, 4, ... without recursion.
+/// Needs a scratch buffer for half of the elements.
+pub fn bottom_up_mergesort<T: PartialOrd>(elements: &mut [T]) {
+    bottom_up_mergesort_impl(elements, &mut |a: &T, b: &T| a < b);
+}
+
+/// Like `bottom_up_mergesort`, but with a comparator function
+pub fn bottom_up_mergesort_by<T, F: FnMut(&T, &T) -> Ordering>(elements: &mut [T], mut compare: F) {
+    bottom_up_mergesort_impl(elements, &mut |a: &T, b: &T| {
+        compare(a, b) == Ordering::Less
+    });
+}
+
+/// Sorts the elements stabelly without allocating. Runs are merged by rotating them into each other,
+/// which takes O(n log² n) time.
+pub fn in_place_mergesort<T: PartialOrd>(elements: &mut [T]) {
+    in_place_mergesort_impl(elements, &mut |a: &T, b: &T| a < b);
+}
+
+/// Like `in_place_mergesort`, but with a comparator function
+pub fn in_place_mergesort_by<T, F: FnMut(&T, &T) -> Ordering>(elements: &mut [T], mut compare: F) {
+    in_place_mergesort_impl(elements, &mut |a: &T, b: &T| {
+        compare(a, b) == Ordering::Less
+    });
+}
+
+pub(crate) fn mergesort_impl<T, F: FnMut(&T, &T) -> bool, P: SortProbe>(
+    elements: &mut [T],
+    is_less: &mut F,
+    probe: &mut P,
+) {
+    let mut buffer = Vec::with_capacity(elements.len() / 2);
+    probe.allocated(buffer.capacity() * std::mem::size_of::<T>());
+    mergesort_recursive(elements, &mut buffer, is_less, probe);
+}
+
+fn mergesort_recursive<T, F: FnMut(&T, &T) -> bool, P: SortProbe>(
+    elements: &mut [T],
+    buffer: &mut Vec<T>,
+    is_less: &mut F,
+    probe: &mut P,
+) {
+    if elements.len() <= 1 {
+        return;
+    }
+    probe.entered();
+    let mid = elements.len() / 2;
+    mergesort_recursive(&mut elements[..mid], buffer, is_less, probe);
+    mergesort_recursive(&mut elements[mid..], buffer, is_less, probe);
+    merge(elements, mid, buffer, is_less, probe);
+    probe.returned();
+}
+
+fn bottom_up_mergesort_impl<T, F: FnMut(&T, &T) -> bool>(elements: &mut [T], is_less: &mut F) {
+    let len = elements.len();
+    let mut buffer = Vec::with_capacity(len / 2);
+    let mut width = 1;
+    while width < len {
+        let mut start = 0;
+        while start + width < len {
+            let end = usize::min(start + 2 * width, len);
+            merge(&mut elements[start..end], width, &mut buffer, is_less, &mut NoProbe);
+            start = end;
+        }
+        width *= 2;
+    }
+}
+
+fn in_place_mergesort_impl<T, F: FnMut(&T, &T) -> bool>(elements: &mut [T], is_less: &mut F) {
+    if elements.len() <= 1 {
+        return;
+    }
+    let mid = elements.len() / 2;
+    in_place_mergesort_impl(&mut elements[..mid], is_less);
+    in_place_mergesort_impl(&mut elements[mid..], is_less);
+    in_place_merge(elements, mid, is_less);
+}
+
+/// Merges the sorted runs `elements[..mid]` and `elements[mid..]` stabelly.
+/// The shorter run is moved into the spare capacity of `buffer`, which has to hold at least
+/// `min(mid, elements.len() - mid)` elements.
+pub(crate) fn merge<T, F: FnMut(&T, &T) -> bool, P: SortProbe>(
+    elements: &mut [T],
+    mid: usize,
+    buffer: &mut Vec<T>,
+    is_less: &mut F,
+    probe: &mut P,
+) {
+    let len = elements.len();
+    if mid == 0 || mid == len || !is_less(&elements[mid], &elements[mid - 1]) {
+        return;
+    }
+    assert!(buffer.capacity() - buffer.len() >= usize::min(mid, len - mid));
+
+    // SAFETY: the shorter run is moved into the buffer, which leaves a gap of the same size in the slice.
+    // Every step moves one element into the gap from the side it is merged from, so the gap always sits
+    // between the elements that are merged and the elements that are still in the slice.
+    // If is_less panics, MergeHole moves the rest of the buffer into the gap.
+    unsafe {
+        let base = elements.as_mut_ptr();
+        let scratch = buffer.as_mut_ptr().add(buffer.len());
+
+        if mid <= len - mid {
+            // merge front to back
+            ptr::copy_nonoverlapping(base, scratch, mid);
+            let mut hole = MergeHole {
+                start: scratch,
+                end: scratch.add(mid),
+                dest: base,
+            };
+            let mut right = base.add(mid);
+            let right_end = base.add(len);
+
+            while hole.start < hole.end && right < right_end {
+                // only take from the right run if it is strictly less, so equal elements keep their order
+                let take_right = is_less(&*right, &*hole.start);
+                let source = if take_right { right } else { hole.start };
+                ptr::copy_nonoverlapping(source, hole.dest, 1);
+                hole.dest = hole.dest.add(1);
+                if take_right {
+                    right = right.add(1);
+                } else {
+                    hole.start = hole.start.add(1);
+                }
+            }
+            // the buffered run is written twice, the right run only as far as it was merged
+            probe.wrote(2 * mid + right.offset_from(base.add(mid)) as usize);
+        } else {
+            // merge back to front, the gap starts behind the remaining left run
+            ptr::copy_nonoverlapping(base.add(mid), scratch, len - mid);
+            let mut hole = MergeHole {
+                start: scratch,
+                end: scratch.add(len - mid),
+                dest: base.add(mid),
+            };
+            let mut out = base.add(len);
+
+            while base < hole.dest && hole.start < hole.end {
+                let left_last = hole.dest.sub(1);
+                let right_last = hole.end.sub(1);
+                // only take from the left run if it is strictly greater, so equal elements keep their order
+                let take_left = is_less(&*right_last, &*left_last);
+                out = out.sub(1);
+                if take_left {
+                    ptr::copy_nonoverlapping(left_last, out, 1);
+                    hole.dest = left_last;
+                } else {
+                    ptr::copy_nonoverlapping(right_last, out, 1);
+                    hole.end = right_last;
+                }
+            }
+            probe.wrote(2 * (len - mid) + base.add(mid).offset_from(hole.dest) as usize);
+        }
+    }
+}
+
+/// The part of the buffered run that still sits in the buffer, and where it has to go
+pub(crate) struct MergeHole<T> {
+    pub(crate) start: *mut T,
+    pub(crate) end: *mut T,
+    pub(crate) dest: *mut T,
+}
+
+impl<T> Drop for MergeHole<T> {
+    fn drop(&mut self) {
+        // SAFETY: the gap in the slice is exactly as long as the part that is left in the buffer
+        unsafe {
+            let remaining = self.end.offset_from(self.start) as usize;
+            ptr::copy_nonoverlapping(self.start, self.dest, remaining);
+        }
+    }
+}
+
+/// Merges the sorted runs `elements[..mid]` and `elements[mid..]` stabelly without extra memory.
+/// The longer run is cut in half, the matching cut in the other run is searched for, and the two middle
+/// parts are rotated past each other. Both halves are then merged recursively.
+fn in_place_merge<T, F: FnMut(&T, &T) -> bool>(elements: &mut [T], mid: usize, is_less: &mut F) {
+    let len = elements.len();
+    if mid == 0 || mid == len || !is_less(&elements[mid], &elements[mid - 1]) {
+        return;
+    }
+    if len == 2 {
+        elements.swap(0, 1);
+        return;
+    }
+
+    let (first_cut, second_cut) = if mid >= len - mid {
+        let first_cut = mid / 2;
+        let pivot = &elements[first_cut];
+        // the elements of the right run that are strictly less than the pivot move in front of it
+        let second_cut = mid + elements[mid..].partition_point(|element| is_less(element, pivot));
+        (first_cut, second_cut)
+    } else {
+        let second_cut = mid + (len - mid) / 2;
+        let pivot = &elements[second_cut];
+        // the elements of the left run that are strictly greater than the pivot move behind it
+        let first_cut = elements[..mid].partition_point(|element| !is_less(pivot, element));
+        (first_cut, second_cut)
+    };
+
+    elements[first_cut..second_cut].rotate_left(mid - first_cut);
+    let new_mid = first_cut + (second_cut - mid);
+    let (left, right) = elements.split_at_mut(new_mid);
+    in_place_merge(left, first_cut, is_less);
+    in_place_merge(right, mid - first_cut, is_less);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sorting::test_helpers::{
+        self, is_stabelly_sorted, random_comparable_list, test_panic_safety, test_unstable_sort,
+    };
+
+    use super::*;
+
+    test_helpers::basic_sorting_tests!(mergesort);
+
+    mod bottom_up {
+        use super::*;
+        test_helpers::basic_sorting_tests!(bottom_up_mergesort);
+    }
+
+    mod in_place {
+        use super::*;
+        test_helpers::basic_sorting_tests!(in_place_mergesort);
+    }
+
+    #[test]
+    fn test_mergesorts_are_stable() {
+        for len in [2, 3, 17, 64, 1000] {
+            let mut data = random_comparable_list(len, 0, 10);
+            mergesort(&mut data);
+            assert!(is_stabelly_sorted(&data));
+
+            let mut data = random_comparable_list(len, 0, 10);
+            bottom_up_mergesort(&mut data);
+            assert!(is_stabelly_sorted(&data));
+
+            let mut data = random_comparable_list(len, 0, 10);
+            in_place_mergesort(&mut data);
+            assert!(is_stabelly_sorted(&data));
+        }
+    }
+
+    #[test]
+    fn test_mergesorts_with_random_items() {
+        test_unstable_sort(&mergesort, 2000, -1000, 1000);
+        test_unstable_sort(&bottom_up_mergesort, 2000, -1000, 1000);
+        test_unstable_sort(&in_place_mergesort, 2000, -1000, 1000);
+    }
+
+    #[test]
+    fn test_mergesorts_sort_completely() {
+        let expected: Vec<i32> = (0..777).collect();
+        let shuffled: Vec<i32> = (0..777).map(|i| (i * 307) % 777).collect();
+
+        let mut items = shuffled.clone();
+        mergesort(&mut items);
+        assert_eq!(items, expected);
+
+        let mut items = shuffled.clone();
+        bottom_up_mergesort(&mut items);
+        assert_eq!(items, expected);
+
+        let mut items = shuffled;
+        in_place_mergesort(&mut items);
+        assert_eq!(items, expected);
+    }
+
+    #[test]
+    fn test_mergesort_by_and_by_key() {
+        let mut items = [3, 1, 4, 1, 5, 9, 2, 6];
+        mergesort_by(&mut items, |a, b| b.cmp(a));
+        assert_eq!(items, [9, 6, 5, 4, 3, 2, 1, 1]);
+
+        let mut items: Vec<(i32, usize)> = [2, 0, 1, 0, 2, 1].iter().cloned().zip(0..).collect();
+        mergesort_by_key(&mut items, |item| item.0);
+        assert_eq!(items, [(0, 1), (0, 3), (1, 2), (1, 5), (2, 0), (2, 4)]);
+
+        let mut items = [3, 1, 4, 1, 5];
+        bottom_up_mergesort_by(&mut items, |a, b| b.cmp(a));
+        assert_eq!(items, [5, 4, 3, 1, 1]);
+
+        let mut items = [3, 1, 4, 1, 5];
+        in_place_mergesort_by(&mut items, |a, b| b.cmp(a));
+        assert_eq!(items, [5, 4, 3, 1, 1]);
+    }
+
+    #[test]
+    fn test_mergesort_reuses_the_buffer() {
+        let mut buffer = Vec::with_capacity(1000);
+        let capacity = buffer.capacity();
+        for len in [10, 500, 2000] {
+            let mut data = random_comparable_list(len, 0, 50);
+            mergesort_with_buffer(&mut data, &mut buffer);
+            assert!(is_stabelly_sorted(&data));
+        }
+        assert!(buffer.is_empty());
+        assert!(buffer.capacity() >= capacity);
+    }
+
+    #[test]
+    fn test_mergesort_moves_owned_values() {
+        let mut items: Vec<String> = (0..300).map(|i| ((i * 31) % 300).to_string()).collect();
+        let mut expected = items.clone();
+        expected.sort();
+        mergesort(&mut items);
+        assert_eq!(items, expected);
+    }
+
+    #[test]
+    fn test_mergesorts_are_panic_safe() {
+        test_panic_safety(mergesort, 40);
+        test_panic_safety(bottom_up_mergesort, 40);
+        test_panic_safety(in_place_mergesort, 40);
+    }
+
+    #[test]
+    fn test_mergesort_conformance() {
+        test_helpers::test_stable_sort_conformance(mergesort);
+        test_helpers::test_stable_sort_conformance(bottom_up_mergesort);
+        test_helpers::test_stable_sort_conformance(in_place_mergesort);
+        test_helpers::test_sort_conformance(mergesort::<f32>);
+    }
+}
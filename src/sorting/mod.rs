@@ -1,11 +1,120 @@
 mod bubblesort;
-pub use bubblesort::bubblesort;
+pub use bubblesort::{bubblesort, bubblesort_by, bubblesort_by_cached_key, bubblesort_by_key};
+
+mod insertionsort;
+pub use insertionsort::{
+    binary_insertionsort, binary_insertionsort_by, binary_insertionsort_by_key, insertionsort,
+    insertionsort_by, insertionsort_by_key,
+};
+
+mod shellsort;
+pub use shellsort::{shellsort, shellsort_by, shellsort_by_with, shellsort_with, GapSequence};
 
 mod quicksort;
-pub use quicksort::quicksort;
+pub use quicksort::{
+    introsort, introsort_by, introsort_by_key, quicksort, quicksort_by, quicksort_by_cached_key,
+    quicksort_by_key, quicksort_by_with, quicksort_with,
+};
+
+mod dual_pivot;
+pub use dual_pivot::{dual_pivot_quicksort, dual_pivot_quicksort_by, dual_pivot_quicksort_by_key};
+
+mod block_partition;
+pub use block_partition::{block_quicksort, block_quicksort_by, block_quicksort_by_key};
+
+mod pdqsort;
+pub use pdqsort::{
+    pdqsort, pdqsort_by, pdqsort_by_key, sort_unstable, sort_unstable_by, sort_unstable_by_key,
+};
+
+mod mergesort;
+pub use mergesort::{
+    bottom_up_mergesort, bottom_up_mergesort_by, in_place_mergesort, in_place_mergesort_by,
+    mergesort, mergesort_by, mergesort_by_key, mergesort_with_buffer,
+};
+
+mod timsort;
+pub use timsort::{timsort, timsort_by, timsort_by_key};
+
+mod pivot;
+pub use pivot::{MedianOfThree, MiddlePivot, Ninther, PivotStrategy, RandomPivot};
+
+mod partial_order;
+pub use partial_order::{
+    bubblesort_floats, bubblesort_partial, quicksort_floats, quicksort_partial, NanPolicy,
+    PartialOrderError, TotalOrder,
+};
+
+mod heapsort;
+pub use heapsort::{heapsort, heapsort_by, heapsort_by_key};
+
+mod parallel;
+pub use parallel::{
+    par_mergesort, par_mergesort_by, par_quicksort, par_quicksort_by, ParallelConfig,
+};
+
+mod selection;
+pub use selection::{
+    median, partial_sort, partial_sort_by, select_nth, select_nth_by, select_nth_with, top_k,
+    top_k_by, SelectionMode,
+};
+
+mod radixsort;
+pub use radixsort::{
+    msd_radix_sort, radix_sort, radix_sort_by_key, radix_sort_with_digit_bits, RadixKey,
+};
+
+mod countingsort;
+pub use countingsort::{
+    counting_sort, counting_sort_by_key, pigeonhole_sort, pigeonhole_sort_by_key, BoundedKeyError,
+    BOUNDED_KEY_MEMORY_LIMIT,
+};
+
+mod bucketsort;
+pub use bucketsort::{bucket_sort, bucket_sort_by_key, bucket_sort_with};
+
+mod instrumented;
+pub use instrumented::{
+    binary_insertionsort_instrumented, bubblesort_instrumented, heapsort_instrumented,
+    insertionsort_instrumented, mergesort_instrumented, quicksort_instrumented,
+    shellsort_instrumented, ComparisonCounter, SortStats,
+};
+
+mod trace;
+pub use trace::{bubblesort_traced, quicksort_traced, SortEvent, SortObserver, TextRenderer};
+
+mod permutation;
+pub use permutation::{
+    apply_permutation_in_place, argsort, argsort_by, argsort_stable, argsort_stable_by,
+    invert_permutation, is_permutation,
+};
+
+mod measures;
+pub use measures::{
+    count_inversions, count_inversions_by, count_runs, count_runs_by, kendall_tau_distance,
+    longest_increasing_subsequence_len, longest_increasing_subsequence_len_by,
+};
+
+mod sorted_iter;
+pub use sorted_iter::{
+    dedup_sorted, difference_sorted, intersect_sorted, is_sorted, is_sorted_by, merge_sorted,
+    merge_sorted_by, union_sorted, DedupSorted, DifferenceSorted, IntersectSorted, MergeSorted,
+    UnionSorted,
+};
+
+mod external;
+pub use external::{
+    external_sort, external_sort_by, ExternalSortConfig, FixedSizeCodec, RecordCodec,
+};
 
+mod network;
+pub use network::{
+    bitonic_sort, bitonic_sort_by, odd_even_merge_sort, odd_even_merge_sort_by, sort_network,
+    sort_network_by,
+};
 
+mod cached_key;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "testkit"))]
 #[macro_use]
 pub mod test_helpers;
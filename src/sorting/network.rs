@@ -0,0 +1,2905 @@
+use std::cmp::Ordering;
+
+/// The largest array `sort_network` sorts
+const MAX_NETWORK_SIZE: usize = 32;
+
+/// The number of comparators of the network for `MAX_NETWORK_SIZE` elements, which is the largest one
+const MAX_COMPARATORS: usize = 185;
+
+/// The networks with the fewest comparators known for up to 16 elements. Up to 12 elements they are
+/// proven to be optimal. Above 16 elements each network sorts two parts with the smaller networks and
+/// joins them with Batcher's odd-even merge, which matches the fewest known comparators for 27 and 29
+/// to 32 elements and needs up to 3 more for the others. Each pair of indexes is a comparator, which puts the
+/// smaller element first.
+const BEST_KNOWN_NETWORKS: [&[(usize, usize)]; MAX_NETWORK_SIZE + 1] = [
+    &[],
+    &[],
+    &[(0, 1)],
+    &[(0, 2), (0, 1), (1, 2)],
+    &[(0, 1), (2, 3), (0, 2), (1, 3), (1, 2)],
+    &[
+        (0, 3),
+        (1, 4),
+        (0, 2),
+        (1, 3),
+        (0, 1),
+        (2, 4),
+        (1, 2),
+        (3, 4),
+        (2, 3),
+    ],
+    &[
+        (0, 5),
+        (1, 3),
+        (2, 4),
+        (1, 2),
+        (3, 4),
+        (0, 3),
+        (2, 5),
+        (0, 1),
+        (2, 3),
+        (4, 5),
+        (1, 2),
+        (3, 4),
+    ],
+    &[
+        (0, 6),
+        (2, 3),
+        (4, 5),
+        (0, 2),
+        (1, 4),
+        (3, 6),
+        (0, 1),
+        (2, 5),
+        (3, 4),
+        (1, 2),
+        (4, 6),
+        (2, 3),
+        (4, 5),
+        (1, 2),
+        (3, 4),
+        (5, 6),
+    ],
+    &[
+        (0, 2),
+        (1, 3),
+        (4, 6),
+        (5, 7),
+        (0, 4),
+        (1, 5),
+        (2, 6),
+        (3, 7),
+        (0, 1),
+        (2, 3),
+        (4, 5),
+        (6, 7),
+        (2, 4),
+        (3, 5),
+        (1, 4),
+        (3, 6),
+        (1, 2),
+        (3, 4),
+        (5, 6),
+    ],
+    &[
+        (0, 3),
+        (1, 7),
+        (2, 5),
+        (4, 8),
+        (0, 7),
+        (2, 4),
+        (3, 8),
+        (5, 6),
+        (0, 2),
+        (1, 3),
+        (4, 5),
+        (7, 8),
+        (1, 4),
+        (3, 6),
+        (5, 7),
+        (0, 1),
+        (2, 4),
+        (3, 5),
+        (6, 8),
+        (2, 3),
+        (4, 5),
+        (6, 7),
+        (1, 2),
+        (3, 4),
+        (5, 6),
+    ],
+    &[
+        (0, 8),
+        (1, 9),
+        (2, 7),
+        (3, 5),
+        (4, 6),
+        (0, 2),
+        (1, 4),
+        (5, 8),
+        (7, 9),
+        (0, 3),
+        (2, 4),
+        (5, 7),
+        (6, 9),
+        (0, 1),
+        (3, 6),
+        (8, 9),
+        (1, 5),
+        (2, 3),
+        (4, 8),
+        (6, 7),
+        (1, 2),
+        (3, 5),
+        (4, 6),
+        (7, 8),
+        (2, 3),
+        (4, 5),
+        (6, 7),
+        (3, 4),
+        (5, 6),
+    ],
+    &[
+        (0, 9),
+        (1, 6),
+        (2, 4),
+        (3, 7),
+        (5, 8),
+        (0, 1),
+        (3, 5),
+        (4, 10),
+        (6, 9),
+        (7, 8),
+        (1, 3),
+        (2, 5),
+        (4, 7),
+        (8, 10),
+        (0, 4),
+        (1, 2),
+        (3, 7),
+        (5, 9),
+        (6, 8),
+        (0, 1),
+        (2, 6),
+        (4, 5),
+        (7, 8),
+        (9, 10),
+        (2, 4),
+        (3, 6),
+        (5, 7),
+        (8, 9),
+        (1, 2),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (2, 3),
+        (4, 5),
+        (6, 7),
+    ],
+    &[
+        (0, 8),
+        (1, 7),
+        (2, 6),
+        (3, 11),
+        (4, 10),
+        (5, 9),
+        (0, 1),
+        (2, 5),
+        (3, 4),
+        (6, 9),
+        (7, 8),
+        (10, 11),
+        (0, 2),
+        (1, 6),
+        (5, 10),
+        (9, 11),
+        (0, 3),
+        (1, 2),
+        (4, 6),
+        (5, 7),
+        (8, 11),
+        (9, 10),
+        (1, 4),
+        (3, 5),
+        (6, 8),
+        (7, 10),
+        (1, 3),
+        (2, 5),
+        (6, 9),
+        (8, 10),
+        (2, 3),
+        (4, 5),
+        (6, 7),
+        (8, 9),
+        (4, 6),
+        (5, 7),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+    ],
+    &[
+        (0, 12),
+        (1, 10),
+        (2, 9),
+        (3, 7),
+        (5, 11),
+        (6, 8),
+        (1, 6),
+        (2, 3),
+        (4, 11),
+        (7, 9),
+        (8, 10),
+        (0, 4),
+        (1, 2),
+        (3, 6),
+        (7, 8),
+        (9, 10),
+        (11, 12),
+        (4, 6),
+        (5, 9),
+        (8, 11),
+        (10, 12),
+        (0, 5),
+        (3, 8),
+        (4, 7),
+        (6, 11),
+        (9, 10),
+        (0, 1),
+        (2, 5),
+        (6, 9),
+        (7, 8),
+        (10, 11),
+        (1, 3),
+        (2, 4),
+        (5, 6),
+        (9, 10),
+        (1, 2),
+        (3, 4),
+        (5, 7),
+        (6, 8),
+        (2, 3),
+        (4, 5),
+        (6, 7),
+        (8, 9),
+        (3, 4),
+        (5, 6),
+    ],
+    &[
+        (0, 1),
+        (2, 3),
+        (4, 5),
+        (6, 7),
+        (8, 9),
+        (10, 11),
+        (12, 13),
+        (0, 2),
+        (1, 3),
+        (4, 8),
+        (5, 9),
+        (10, 12),
+        (11, 13),
+        (0, 4),
+        (1, 2),
+        (3, 7),
+        (5, 8),
+        (6, 10),
+        (9, 13),
+        (11, 12),
+        (0, 6),
+        (1, 5),
+        (3, 9),
+        (4, 10),
+        (7, 13),
+        (8, 12),
+        (2, 10),
+        (3, 11),
+        (4, 6),
+        (7, 9),
+        (1, 3),
+        (2, 8),
+        (5, 11),
+        (6, 7),
+        (10, 12),
+        (1, 4),
+        (2, 6),
+        (3, 5),
+        (7, 11),
+        (8, 10),
+        (9, 12),
+        (2, 4),
+        (3, 6),
+        (5, 8),
+        (7, 10),
+        (9, 11),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (9, 10),
+        (6, 7),
+    ],
+    &[
+        (0, 13),
+        (1, 12),
+        (3, 14),
+        (4, 8),
+        (5, 6),
+        (7, 11),
+        (9, 10),
+        (0, 5),
+        (1, 7),
+        (2, 9),
+        (3, 4),
+        (6, 13),
+        (8, 14),
+        (11, 12),
+        (0, 1),
+        (2, 3),
+        (4, 5),
+        (6, 8),
+        (7, 9),
+        (10, 11),
+        (12, 13),
+        (0, 2),
+        (1, 3),
+        (4, 10),
+        (5, 11),
+        (6, 7),
+        (8, 9),
+        (12, 14),
+        (1, 2),
+        (3, 12),
+        (4, 6),
+        (5, 7),
+        (8, 10),
+        (9, 11),
+        (13, 14),
+        (1, 4),
+        (2, 6),
+        (5, 8),
+        (7, 10),
+        (9, 13),
+        (11, 14),
+        (2, 4),
+        (3, 6),
+        (9, 12),
+        (11, 13),
+        (3, 5),
+        (6, 8),
+        (7, 9),
+        (10, 12),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (9, 10),
+        (11, 12),
+        (6, 7),
+        (8, 9),
+    ],
+    &[
+        (0, 13),
+        (1, 12),
+        (2, 15),
+        (3, 14),
+        (4, 8),
+        (5, 6),
+        (7, 11),
+        (9, 10),
+        (0, 5),
+        (1, 7),
+        (2, 9),
+        (3, 4),
+        (6, 13),
+        (8, 14),
+        (10, 15),
+        (11, 12),
+        (0, 1),
+        (2, 3),
+        (4, 5),
+        (6, 8),
+        (7, 9),
+        (10, 11),
+        (12, 13),
+        (14, 15),
+        (0, 2),
+        (1, 3),
+        (4, 10),
+        (5, 11),
+        (6, 7),
+        (8, 9),
+        (12, 14),
+        (13, 15),
+        (1, 2),
+        (3, 12),
+        (4, 6),
+        (5, 7),
+        (8, 10),
+        (9, 11),
+        (13, 14),
+        (1, 4),
+        (2, 6),
+        (5, 8),
+        (7, 10),
+        (9, 13),
+        (11, 14),
+        (2, 4),
+        (3, 6),
+        (9, 12),
+        (11, 13),
+        (3, 5),
+        (6, 8),
+        (7, 9),
+        (10, 12),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (9, 10),
+        (11, 12),
+        (6, 7),
+        (8, 9),
+    ],
+    &[
+        (0, 2),
+        (1, 3),
+        (4, 6),
+        (5, 7),
+        (0, 4),
+        (1, 5),
+        (2, 6),
+        (3, 7),
+        (0, 1),
+        (2, 3),
+        (4, 5),
+        (6, 7),
+        (2, 4),
+        (3, 5),
+        (1, 4),
+        (3, 6),
+        (1, 2),
+        (3, 4),
+        (5, 6),
+        (8, 11),
+        (9, 15),
+        (10, 13),
+        (12, 16),
+        (8, 15),
+        (10, 12),
+        (11, 16),
+        (13, 14),
+        (8, 10),
+        (9, 11),
+        (12, 13),
+        (15, 16),
+        (9, 12),
+        (11, 14),
+        (13, 15),
+        (8, 9),
+        (10, 12),
+        (11, 13),
+        (14, 16),
+        (10, 11),
+        (12, 13),
+        (14, 15),
+        (9, 10),
+        (11, 12),
+        (13, 14),
+        (0, 8),
+        (8, 16),
+        (4, 12),
+        (4, 8),
+        (12, 16),
+        (2, 10),
+        (6, 14),
+        (6, 10),
+        (2, 4),
+        (6, 8),
+        (10, 12),
+        (14, 16),
+        (1, 9),
+        (5, 13),
+        (5, 9),
+        (3, 11),
+        (7, 15),
+        (7, 11),
+        (3, 5),
+        (7, 9),
+        (11, 13),
+        (1, 2),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (9, 10),
+        (11, 12),
+        (13, 14),
+        (15, 16),
+    ],
+    &[
+        (0, 2),
+        (1, 3),
+        (4, 6),
+        (5, 7),
+        (0, 4),
+        (1, 5),
+        (2, 6),
+        (3, 7),
+        (0, 1),
+        (2, 3),
+        (4, 5),
+        (6, 7),
+        (2, 4),
+        (3, 5),
+        (1, 4),
+        (3, 6),
+        (1, 2),
+        (3, 4),
+        (5, 6),
+        (8, 16),
+        (9, 17),
+        (10, 15),
+        (11, 13),
+        (12, 14),
+        (8, 10),
+        (9, 12),
+        (13, 16),
+        (15, 17),
+        (8, 11),
+        (10, 12),
+        (13, 15),
+        (14, 17),
+        (8, 9),
+        (11, 14),
+        (16, 17),
+        (9, 13),
+        (10, 11),
+        (12, 16),
+        (14, 15),
+        (9, 10),
+        (11, 13),
+        (12, 14),
+        (15, 16),
+        (10, 11),
+        (12, 13),
+        (14, 15),
+        (11, 12),
+        (13, 14),
+        (0, 8),
+        (8, 16),
+        (4, 12),
+        (4, 8),
+        (12, 16),
+        (2, 10),
+        (6, 14),
+        (6, 10),
+        (2, 4),
+        (6, 8),
+        (10, 12),
+        (14, 16),
+        (1, 9),
+        (9, 17),
+        (5, 13),
+        (5, 9),
+        (13, 17),
+        (3, 11),
+        (7, 15),
+        (7, 11),
+        (3, 5),
+        (7, 9),
+        (11, 13),
+        (15, 17),
+        (1, 2),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (9, 10),
+        (11, 12),
+        (13, 14),
+        (15, 16),
+    ],
+    &[
+        (0, 3),
+        (1, 7),
+        (2, 5),
+        (4, 8),
+        (0, 7),
+        (2, 4),
+        (3, 8),
+        (5, 6),
+        (0, 2),
+        (1, 3),
+        (4, 5),
+        (7, 8),
+        (1, 4),
+        (3, 6),
+        (5, 7),
+        (0, 1),
+        (2, 4),
+        (3, 5),
+        (6, 8),
+        (2, 3),
+        (4, 5),
+        (6, 7),
+        (1, 2),
+        (3, 4),
+        (5, 6),
+        (9, 17),
+        (10, 18),
+        (11, 16),
+        (12, 14),
+        (13, 15),
+        (9, 11),
+        (10, 13),
+        (14, 17),
+        (16, 18),
+        (9, 12),
+        (11, 13),
+        (14, 16),
+        (15, 18),
+        (9, 10),
+        (12, 15),
+        (17, 18),
+        (10, 14),
+        (11, 12),
+        (13, 17),
+        (15, 16),
+        (10, 11),
+        (12, 14),
+        (13, 15),
+        (16, 17),
+        (11, 12),
+        (13, 14),
+        (15, 16),
+        (12, 13),
+        (14, 15),
+        (0, 9),
+        (8, 17),
+        (8, 9),
+        (4, 13),
+        (4, 8),
+        (9, 13),
+        (2, 11),
+        (6, 15),
+        (6, 11),
+        (2, 4),
+        (6, 8),
+        (9, 11),
+        (13, 15),
+        (1, 10),
+        (10, 18),
+        (5, 14),
+        (5, 10),
+        (14, 18),
+        (3, 12),
+        (7, 16),
+        (7, 12),
+        (3, 5),
+        (7, 10),
+        (12, 14),
+        (16, 18),
+        (1, 2),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (9, 10),
+        (11, 12),
+        (13, 14),
+        (15, 16),
+        (17, 18),
+    ],
+    &[
+        (0, 8),
+        (1, 9),
+        (2, 7),
+        (3, 5),
+        (4, 6),
+        (0, 2),
+        (1, 4),
+        (5, 8),
+        (7, 9),
+        (0, 3),
+        (2, 4),
+        (5, 7),
+        (6, 9),
+        (0, 1),
+        (3, 6),
+        (8, 9),
+        (1, 5),
+        (2, 3),
+        (4, 8),
+        (6, 7),
+        (1, 2),
+        (3, 5),
+        (4, 6),
+        (7, 8),
+        (2, 3),
+        (4, 5),
+        (6, 7),
+        (3, 4),
+        (5, 6),
+        (10, 18),
+        (11, 19),
+        (12, 17),
+        (13, 15),
+        (14, 16),
+        (10, 12),
+        (11, 14),
+        (15, 18),
+        (17, 19),
+        (10, 13),
+        (12, 14),
+        (15, 17),
+        (16, 19),
+        (10, 11),
+        (13, 16),
+        (18, 19),
+        (11, 15),
+        (12, 13),
+        (14, 18),
+        (16, 17),
+        (11, 12),
+        (13, 15),
+        (14, 16),
+        (17, 18),
+        (12, 13),
+        (14, 15),
+        (16, 17),
+        (13, 14),
+        (15, 16),
+        (0, 10),
+        (8, 18),
+        (8, 10),
+        (4, 14),
+        (4, 8),
+        (10, 14),
+        (2, 12),
+        (6, 16),
+        (6, 12),
+        (2, 4),
+        (6, 8),
+        (10, 12),
+        (14, 16),
+        (1, 11),
+        (9, 19),
+        (9, 11),
+        (5, 15),
+        (5, 9),
+        (11, 15),
+        (3, 13),
+        (7, 17),
+        (7, 13),
+        (3, 5),
+        (7, 9),
+        (11, 13),
+        (15, 17),
+        (1, 2),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (9, 10),
+        (11, 12),
+        (13, 14),
+        (15, 16),
+        (17, 18),
+    ],
+    &[
+        (0, 8),
+        (1, 9),
+        (2, 7),
+        (3, 5),
+        (4, 6),
+        (0, 2),
+        (1, 4),
+        (5, 8),
+        (7, 9),
+        (0, 3),
+        (2, 4),
+        (5, 7),
+        (6, 9),
+        (0, 1),
+        (3, 6),
+        (8, 9),
+        (1, 5),
+        (2, 3),
+        (4, 8),
+        (6, 7),
+        (1, 2),
+        (3, 5),
+        (4, 6),
+        (7, 8),
+        (2, 3),
+        (4, 5),
+        (6, 7),
+        (3, 4),
+        (5, 6),
+        (10, 19),
+        (11, 16),
+        (12, 14),
+        (13, 17),
+        (15, 18),
+        (10, 11),
+        (13, 15),
+        (14, 20),
+        (16, 19),
+        (17, 18),
+        (11, 13),
+        (12, 15),
+        (14, 17),
+        (18, 20),
+        (10, 14),
+        (11, 12),
+        (13, 17),
+        (15, 19),
+        (16, 18),
+        (10, 11),
+        (12, 16),
+        (14, 15),
+        (17, 18),
+        (19, 20),
+        (12, 14),
+        (13, 16),
+        (15, 17),
+        (18, 19),
+        (11, 12),
+        (13, 14),
+        (15, 16),
+        (17, 18),
+        (12, 13),
+        (14, 15),
+        (16, 17),
+        (0, 10),
+        (8, 18),
+        (8, 10),
+        (4, 14),
+        (4, 8),
+        (10, 14),
+        (2, 12),
+        (12, 20),
+        (6, 16),
+        (6, 12),
+        (16, 20),
+        (2, 4),
+        (6, 8),
+        (10, 12),
+        (14, 16),
+        (18, 20),
+        (1, 11),
+        (9, 19),
+        (9, 11),
+        (5, 15),
+        (5, 9),
+        (11, 15),
+        (3, 13),
+        (7, 17),
+        (7, 13),
+        (3, 5),
+        (7, 9),
+        (11, 13),
+        (15, 17),
+        (1, 2),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (9, 10),
+        (11, 12),
+        (13, 14),
+        (15, 16),
+        (17, 18),
+        (19, 20),
+    ],
+    &[
+        (0, 8),
+        (1, 9),
+        (2, 7),
+        (3, 5),
+        (4, 6),
+        (0, 2),
+        (1, 4),
+        (5, 8),
+        (7, 9),
+        (0, 3),
+        (2, 4),
+        (5, 7),
+        (6, 9),
+        (0, 1),
+        (3, 6),
+        (8, 9),
+        (1, 5),
+        (2, 3),
+        (4, 8),
+        (6, 7),
+        (1, 2),
+        (3, 5),
+        (4, 6),
+        (7, 8),
+        (2, 3),
+        (4, 5),
+        (6, 7),
+        (3, 4),
+        (5, 6),
+        (10, 18),
+        (11, 17),
+        (12, 16),
+        (13, 21),
+        (14, 20),
+        (15, 19),
+        (10, 11),
+        (12, 15),
+        (13, 14),
+        (16, 19),
+        (17, 18),
+        (20, 21),
+        (10, 12),
+        (11, 16),
+        (15, 20),
+        (19, 21),
+        (10, 13),
+        (11, 12),
+        (14, 16),
+        (15, 17),
+        (18, 21),
+        (19, 20),
+        (11, 14),
+        (13, 15),
+        (16, 18),
+        (17, 20),
+        (11, 13),
+        (12, 15),
+        (16, 19),
+        (18, 20),
+        (12, 13),
+        (14, 15),
+        (16, 17),
+        (18, 19),
+        (14, 16),
+        (15, 17),
+        (13, 14),
+        (15, 16),
+        (17, 18),
+        (0, 10),
+        (8, 18),
+        (8, 10),
+        (4, 14),
+        (4, 8),
+        (10, 14),
+        (2, 12),
+        (12, 20),
+        (6, 16),
+        (6, 12),
+        (16, 20),
+        (2, 4),
+        (6, 8),
+        (10, 12),
+        (14, 16),
+        (18, 20),
+        (1, 11),
+        (9, 19),
+        (9, 11),
+        (5, 15),
+        (5, 9),
+        (11, 15),
+        (3, 13),
+        (13, 21),
+        (7, 17),
+        (7, 13),
+        (17, 21),
+        (3, 5),
+        (7, 9),
+        (11, 13),
+        (15, 17),
+        (19, 21),
+        (1, 2),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (9, 10),
+        (11, 12),
+        (13, 14),
+        (15, 16),
+        (17, 18),
+        (19, 20),
+    ],
+    &[
+        (0, 9),
+        (1, 6),
+        (2, 4),
+        (3, 7),
+        (5, 8),
+        (0, 1),
+        (3, 5),
+        (4, 10),
+        (6, 9),
+        (7, 8),
+        (1, 3),
+        (2, 5),
+        (4, 7),
+        (8, 10),
+        (0, 4),
+        (1, 2),
+        (3, 7),
+        (5, 9),
+        (6, 8),
+        (0, 1),
+        (2, 6),
+        (4, 5),
+        (7, 8),
+        (9, 10),
+        (2, 4),
+        (3, 6),
+        (5, 7),
+        (8, 9),
+        (1, 2),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (2, 3),
+        (4, 5),
+        (6, 7),
+        (11, 19),
+        (12, 18),
+        (13, 17),
+        (14, 22),
+        (15, 21),
+        (16, 20),
+        (11, 12),
+        (13, 16),
+        (14, 15),
+        (17, 20),
+        (18, 19),
+        (21, 22),
+        (11, 13),
+        (12, 17),
+        (16, 21),
+        (20, 22),
+        (11, 14),
+        (12, 13),
+        (15, 17),
+        (16, 18),
+        (19, 22),
+        (20, 21),
+        (12, 15),
+        (14, 16),
+        (17, 19),
+        (18, 21),
+        (12, 14),
+        (13, 16),
+        (17, 20),
+        (19, 21),
+        (13, 14),
+        (15, 16),
+        (17, 18),
+        (19, 20),
+        (15, 17),
+        (16, 18),
+        (14, 15),
+        (16, 17),
+        (18, 19),
+        (0, 11),
+        (8, 19),
+        (8, 11),
+        (4, 15),
+        (4, 8),
+        (11, 15),
+        (2, 13),
+        (10, 21),
+        (10, 13),
+        (6, 17),
+        (6, 10),
+        (13, 17),
+        (2, 4),
+        (6, 8),
+        (10, 11),
+        (13, 15),
+        (17, 19),
+        (1, 12),
+        (9, 20),
+        (9, 12),
+        (5, 16),
+        (5, 9),
+        (12, 16),
+        (3, 14),
+        (14, 22),
+        (7, 18),
+        (7, 14),
+        (18, 22),
+        (3, 5),
+        (7, 9),
+        (12, 14),
+        (16, 18),
+        (20, 22),
+        (1, 2),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (9, 10),
+        (11, 12),
+        (13, 14),
+        (15, 16),
+        (17, 18),
+        (19, 20),
+        (21, 22),
+    ],
+    &[
+        (0, 8),
+        (1, 7),
+        (2, 6),
+        (3, 11),
+        (4, 10),
+        (5, 9),
+        (0, 1),
+        (2, 5),
+        (3, 4),
+        (6, 9),
+        (7, 8),
+        (10, 11),
+        (0, 2),
+        (1, 6),
+        (5, 10),
+        (9, 11),
+        (0, 3),
+        (1, 2),
+        (4, 6),
+        (5, 7),
+        (8, 11),
+        (9, 10),
+        (1, 4),
+        (3, 5),
+        (6, 8),
+        (7, 10),
+        (1, 3),
+        (2, 5),
+        (6, 9),
+        (8, 10),
+        (2, 3),
+        (4, 5),
+        (6, 7),
+        (8, 9),
+        (4, 6),
+        (5, 7),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (12, 20),
+        (13, 19),
+        (14, 18),
+        (15, 23),
+        (16, 22),
+        (17, 21),
+        (12, 13),
+        (14, 17),
+        (15, 16),
+        (18, 21),
+        (19, 20),
+        (22, 23),
+        (12, 14),
+        (13, 18),
+        (17, 22),
+        (21, 23),
+        (12, 15),
+        (13, 14),
+        (16, 18),
+        (17, 19),
+        (20, 23),
+        (21, 22),
+        (13, 16),
+        (15, 17),
+        (18, 20),
+        (19, 22),
+        (13, 15),
+        (14, 17),
+        (18, 21),
+        (20, 22),
+        (14, 15),
+        (16, 17),
+        (18, 19),
+        (20, 21),
+        (16, 18),
+        (17, 19),
+        (15, 16),
+        (17, 18),
+        (19, 20),
+        (0, 12),
+        (8, 20),
+        (8, 12),
+        (4, 16),
+        (4, 8),
+        (12, 16),
+        (2, 14),
+        (10, 22),
+        (10, 14),
+        (6, 18),
+        (6, 10),
+        (14, 18),
+        (2, 4),
+        (6, 8),
+        (10, 12),
+        (14, 16),
+        (18, 20),
+        (1, 13),
+        (9, 21),
+        (9, 13),
+        (5, 17),
+        (5, 9),
+        (13, 17),
+        (3, 15),
+        (11, 23),
+        (11, 15),
+        (7, 19),
+        (7, 11),
+        (15, 19),
+        (3, 5),
+        (7, 9),
+        (11, 13),
+        (15, 17),
+        (19, 21),
+        (1, 2),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (9, 10),
+        (11, 12),
+        (13, 14),
+        (15, 16),
+        (17, 18),
+        (19, 20),
+        (21, 22),
+    ],
+    &[
+        (0, 8),
+        (1, 7),
+        (2, 6),
+        (3, 11),
+        (4, 10),
+        (5, 9),
+        (0, 1),
+        (2, 5),
+        (3, 4),
+        (6, 9),
+        (7, 8),
+        (10, 11),
+        (0, 2),
+        (1, 6),
+        (5, 10),
+        (9, 11),
+        (0, 3),
+        (1, 2),
+        (4, 6),
+        (5, 7),
+        (8, 11),
+        (9, 10),
+        (1, 4),
+        (3, 5),
+        (6, 8),
+        (7, 10),
+        (1, 3),
+        (2, 5),
+        (6, 9),
+        (8, 10),
+        (2, 3),
+        (4, 5),
+        (6, 7),
+        (8, 9),
+        (4, 6),
+        (5, 7),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (12, 24),
+        (13, 22),
+        (14, 21),
+        (15, 19),
+        (17, 23),
+        (18, 20),
+        (13, 18),
+        (14, 15),
+        (16, 23),
+        (19, 21),
+        (20, 22),
+        (12, 16),
+        (13, 14),
+        (15, 18),
+        (19, 20),
+        (21, 22),
+        (23, 24),
+        (16, 18),
+        (17, 21),
+        (20, 23),
+        (22, 24),
+        (12, 17),
+        (15, 20),
+        (16, 19),
+        (18, 23),
+        (21, 22),
+        (12, 13),
+        (14, 17),
+        (18, 21),
+        (19, 20),
+        (22, 23),
+        (13, 15),
+        (14, 16),
+        (17, 18),
+        (21, 22),
+        (13, 14),
+        (15, 16),
+        (17, 19),
+        (18, 20),
+        (14, 15),
+        (16, 17),
+        (18, 19),
+        (20, 21),
+        (15, 16),
+        (17, 18),
+        (0, 12),
+        (8, 20),
+        (8, 12),
+        (4, 16),
+        (16, 24),
+        (4, 8),
+        (12, 16),
+        (20, 24),
+        (2, 14),
+        (10, 22),
+        (10, 14),
+        (6, 18),
+        (6, 10),
+        (14, 18),
+        (2, 4),
+        (6, 8),
+        (10, 12),
+        (14, 16),
+        (18, 20),
+        (22, 24),
+        (1, 13),
+        (9, 21),
+        (9, 13),
+        (5, 17),
+        (5, 9),
+        (13, 17),
+        (3, 15),
+        (11, 23),
+        (11, 15),
+        (7, 19),
+        (7, 11),
+        (15, 19),
+        (3, 5),
+        (7, 9),
+        (11, 13),
+        (15, 17),
+        (19, 21),
+        (1, 2),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (9, 10),
+        (11, 12),
+        (13, 14),
+        (15, 16),
+        (17, 18),
+        (19, 20),
+        (21, 22),
+        (23, 24),
+    ],
+    &[
+        (0, 12),
+        (1, 10),
+        (2, 9),
+        (3, 7),
+        (5, 11),
+        (6, 8),
+        (1, 6),
+        (2, 3),
+        (4, 11),
+        (7, 9),
+        (8, 10),
+        (0, 4),
+        (1, 2),
+        (3, 6),
+        (7, 8),
+        (9, 10),
+        (11, 12),
+        (4, 6),
+        (5, 9),
+        (8, 11),
+        (10, 12),
+        (0, 5),
+        (3, 8),
+        (4, 7),
+        (6, 11),
+        (9, 10),
+        (0, 1),
+        (2, 5),
+        (6, 9),
+        (7, 8),
+        (10, 11),
+        (1, 3),
+        (2, 4),
+        (5, 6),
+        (9, 10),
+        (1, 2),
+        (3, 4),
+        (5, 7),
+        (6, 8),
+        (2, 3),
+        (4, 5),
+        (6, 7),
+        (8, 9),
+        (3, 4),
+        (5, 6),
+        (13, 25),
+        (14, 23),
+        (15, 22),
+        (16, 20),
+        (18, 24),
+        (19, 21),
+        (14, 19),
+        (15, 16),
+        (17, 24),
+        (20, 22),
+        (21, 23),
+        (13, 17),
+        (14, 15),
+        (16, 19),
+        (20, 21),
+        (22, 23),
+        (24, 25),
+        (17, 19),
+        (18, 22),
+        (21, 24),
+        (23, 25),
+        (13, 18),
+        (16, 21),
+        (17, 20),
+        (19, 24),
+        (22, 23),
+        (13, 14),
+        (15, 18),
+        (19, 22),
+        (20, 21),
+        (23, 24),
+        (14, 16),
+        (15, 17),
+        (18, 19),
+        (22, 23),
+        (14, 15),
+        (16, 17),
+        (18, 20),
+        (19, 21),
+        (15, 16),
+        (17, 18),
+        (19, 20),
+        (21, 22),
+        (16, 17),
+        (18, 19),
+        (0, 13),
+        (8, 21),
+        (8, 13),
+        (4, 17),
+        (12, 25),
+        (12, 17),
+        (4, 8),
+        (12, 13),
+        (17, 21),
+        (2, 15),
+        (10, 23),
+        (10, 15),
+        (6, 19),
+        (6, 10),
+        (15, 19),
+        (2, 4),
+        (6, 8),
+        (10, 12),
+        (13, 15),
+        (17, 19),
+        (21, 23),
+        (1, 14),
+        (9, 22),
+        (9, 14),
+        (5, 18),
+        (5, 9),
+        (14, 18),
+        (3, 16),
+        (11, 24),
+        (11, 16),
+        (7, 20),
+        (7, 11),
+        (16, 20),
+        (3, 5),
+        (7, 9),
+        (11, 14),
+        (16, 18),
+        (20, 22),
+        (1, 2),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (9, 10),
+        (11, 12),
+        (13, 14),
+        (15, 16),
+        (17, 18),
+        (19, 20),
+        (21, 22),
+        (23, 24),
+    ],
+    &[
+        (0, 9),
+        (1, 6),
+        (2, 4),
+        (3, 7),
+        (5, 8),
+        (0, 1),
+        (3, 5),
+        (4, 10),
+        (6, 9),
+        (7, 8),
+        (1, 3),
+        (2, 5),
+        (4, 7),
+        (8, 10),
+        (0, 4),
+        (1, 2),
+        (3, 7),
+        (5, 9),
+        (6, 8),
+        (0, 1),
+        (2, 6),
+        (4, 5),
+        (7, 8),
+        (9, 10),
+        (2, 4),
+        (3, 6),
+        (5, 7),
+        (8, 9),
+        (1, 2),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (2, 3),
+        (4, 5),
+        (6, 7),
+        (11, 24),
+        (12, 23),
+        (13, 26),
+        (14, 25),
+        (15, 19),
+        (16, 17),
+        (18, 22),
+        (20, 21),
+        (11, 16),
+        (12, 18),
+        (13, 20),
+        (14, 15),
+        (17, 24),
+        (19, 25),
+        (21, 26),
+        (22, 23),
+        (11, 12),
+        (13, 14),
+        (15, 16),
+        (17, 19),
+        (18, 20),
+        (21, 22),
+        (23, 24),
+        (25, 26),
+        (11, 13),
+        (12, 14),
+        (15, 21),
+        (16, 22),
+        (17, 18),
+        (19, 20),
+        (23, 25),
+        (24, 26),
+        (12, 13),
+        (14, 23),
+        (15, 17),
+        (16, 18),
+        (19, 21),
+        (20, 22),
+        (24, 25),
+        (12, 15),
+        (13, 17),
+        (16, 19),
+        (18, 21),
+        (20, 24),
+        (22, 25),
+        (13, 15),
+        (14, 17),
+        (20, 23),
+        (22, 24),
+        (14, 16),
+        (17, 19),
+        (18, 20),
+        (21, 23),
+        (14, 15),
+        (16, 17),
+        (18, 19),
+        (20, 21),
+        (22, 23),
+        (17, 18),
+        (19, 20),
+        (0, 11),
+        (8, 19),
+        (8, 11),
+        (4, 15),
+        (15, 23),
+        (4, 8),
+        (11, 15),
+        (19, 23),
+        (2, 13),
+        (10, 21),
+        (10, 13),
+        (6, 17),
+        (17, 25),
+        (6, 10),
+        (13, 17),
+        (21, 25),
+        (2, 4),
+        (6, 8),
+        (10, 11),
+        (13, 15),
+        (17, 19),
+        (21, 23),
+        (1, 12),
+        (9, 20),
+        (9, 12),
+        (5, 16),
+        (16, 24),
+        (5, 9),
+        (12, 16),
+        (20, 24),
+        (3, 14),
+        (14, 22),
+        (7, 18),
+        (18, 26),
+        (7, 14),
+        (18, 22),
+        (3, 5),
+        (7, 9),
+        (12, 14),
+        (16, 18),
+        (20, 22),
+        (24, 26),
+        (1, 2),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (9, 10),
+        (11, 12),
+        (13, 14),
+        (15, 16),
+        (17, 18),
+        (19, 20),
+        (21, 22),
+        (23, 24),
+        (25, 26),
+    ],
+    &[
+        (0, 8),
+        (1, 7),
+        (2, 6),
+        (3, 11),
+        (4, 10),
+        (5, 9),
+        (0, 1),
+        (2, 5),
+        (3, 4),
+        (6, 9),
+        (7, 8),
+        (10, 11),
+        (0, 2),
+        (1, 6),
+        (5, 10),
+        (9, 11),
+        (0, 3),
+        (1, 2),
+        (4, 6),
+        (5, 7),
+        (8, 11),
+        (9, 10),
+        (1, 4),
+        (3, 5),
+        (6, 8),
+        (7, 10),
+        (1, 3),
+        (2, 5),
+        (6, 9),
+        (8, 10),
+        (2, 3),
+        (4, 5),
+        (6, 7),
+        (8, 9),
+        (4, 6),
+        (5, 7),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (12, 25),
+        (13, 24),
+        (14, 27),
+        (15, 26),
+        (16, 20),
+        (17, 18),
+        (19, 23),
+        (21, 22),
+        (12, 17),
+        (13, 19),
+        (14, 21),
+        (15, 16),
+        (18, 25),
+        (20, 26),
+        (22, 27),
+        (23, 24),
+        (12, 13),
+        (14, 15),
+        (16, 17),
+        (18, 20),
+        (19, 21),
+        (22, 23),
+        (24, 25),
+        (26, 27),
+        (12, 14),
+        (13, 15),
+        (16, 22),
+        (17, 23),
+        (18, 19),
+        (20, 21),
+        (24, 26),
+        (25, 27),
+        (13, 14),
+        (15, 24),
+        (16, 18),
+        (17, 19),
+        (20, 22),
+        (21, 23),
+        (25, 26),
+        (13, 16),
+        (14, 18),
+        (17, 20),
+        (19, 22),
+        (21, 25),
+        (23, 26),
+        (14, 16),
+        (15, 18),
+        (21, 24),
+        (23, 25),
+        (15, 17),
+        (18, 20),
+        (19, 21),
+        (22, 24),
+        (15, 16),
+        (17, 18),
+        (19, 20),
+        (21, 22),
+        (23, 24),
+        (18, 19),
+        (20, 21),
+        (0, 12),
+        (8, 20),
+        (8, 12),
+        (4, 16),
+        (16, 24),
+        (4, 8),
+        (12, 16),
+        (20, 24),
+        (2, 14),
+        (10, 22),
+        (10, 14),
+        (6, 18),
+        (18, 26),
+        (6, 10),
+        (14, 18),
+        (22, 26),
+        (2, 4),
+        (6, 8),
+        (10, 12),
+        (14, 16),
+        (18, 20),
+        (22, 24),
+        (1, 13),
+        (9, 21),
+        (9, 13),
+        (5, 17),
+        (17, 25),
+        (5, 9),
+        (13, 17),
+        (21, 25),
+        (3, 15),
+        (11, 23),
+        (11, 15),
+        (7, 19),
+        (19, 27),
+        (7, 11),
+        (15, 19),
+        (23, 27),
+        (3, 5),
+        (7, 9),
+        (11, 13),
+        (15, 17),
+        (19, 21),
+        (23, 25),
+        (1, 2),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (9, 10),
+        (11, 12),
+        (13, 14),
+        (15, 16),
+        (17, 18),
+        (19, 20),
+        (21, 22),
+        (23, 24),
+        (25, 26),
+    ],
+    &[
+        (0, 12),
+        (1, 10),
+        (2, 9),
+        (3, 7),
+        (5, 11),
+        (6, 8),
+        (1, 6),
+        (2, 3),
+        (4, 11),
+        (7, 9),
+        (8, 10),
+        (0, 4),
+        (1, 2),
+        (3, 6),
+        (7, 8),
+        (9, 10),
+        (11, 12),
+        (4, 6),
+        (5, 9),
+        (8, 11),
+        (10, 12),
+        (0, 5),
+        (3, 8),
+        (4, 7),
+        (6, 11),
+        (9, 10),
+        (0, 1),
+        (2, 5),
+        (6, 9),
+        (7, 8),
+        (10, 11),
+        (1, 3),
+        (2, 4),
+        (5, 6),
+        (9, 10),
+        (1, 2),
+        (3, 4),
+        (5, 7),
+        (6, 8),
+        (2, 3),
+        (4, 5),
+        (6, 7),
+        (8, 9),
+        (3, 4),
+        (5, 6),
+        (13, 26),
+        (14, 25),
+        (15, 28),
+        (16, 27),
+        (17, 21),
+        (18, 19),
+        (20, 24),
+        (22, 23),
+        (13, 18),
+        (14, 20),
+        (15, 22),
+        (16, 17),
+        (19, 26),
+        (21, 27),
+        (23, 28),
+        (24, 25),
+        (13, 14),
+        (15, 16),
+        (17, 18),
+        (19, 21),
+        (20, 22),
+        (23, 24),
+        (25, 26),
+        (27, 28),
+        (13, 15),
+        (14, 16),
+        (17, 23),
+        (18, 24),
+        (19, 20),
+        (21, 22),
+        (25, 27),
+        (26, 28),
+        (14, 15),
+        (16, 25),
+        (17, 19),
+        (18, 20),
+        (21, 23),
+        (22, 24),
+        (26, 27),
+        (14, 17),
+        (15, 19),
+        (18, 21),
+        (20, 23),
+        (22, 26),
+        (24, 27),
+        (15, 17),
+        (16, 19),
+        (22, 25),
+        (24, 26),
+        (16, 18),
+        (19, 21),
+        (20, 22),
+        (23, 25),
+        (16, 17),
+        (18, 19),
+        (20, 21),
+        (22, 23),
+        (24, 25),
+        (19, 20),
+        (21, 22),
+        (0, 13),
+        (8, 21),
+        (8, 13),
+        (4, 17),
+        (12, 25),
+        (12, 17),
+        (4, 8),
+        (12, 13),
+        (17, 21),
+        (2, 15),
+        (10, 23),
+        (10, 15),
+        (6, 19),
+        (19, 27),
+        (6, 10),
+        (15, 19),
+        (23, 27),
+        (2, 4),
+        (6, 8),
+        (10, 12),
+        (13, 15),
+        (17, 19),
+        (21, 23),
+        (25, 27),
+        (1, 14),
+        (9, 22),
+        (9, 14),
+        (5, 18),
+        (18, 26),
+        (5, 9),
+        (14, 18),
+        (22, 26),
+        (3, 16),
+        (11, 24),
+        (11, 16),
+        (7, 20),
+        (20, 28),
+        (7, 11),
+        (16, 20),
+        (24, 28),
+        (3, 5),
+        (7, 9),
+        (11, 14),
+        (16, 18),
+        (20, 22),
+        (24, 26),
+        (1, 2),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (9, 10),
+        (11, 12),
+        (13, 14),
+        (15, 16),
+        (17, 18),
+        (19, 20),
+        (21, 22),
+        (23, 24),
+        (25, 26),
+        (27, 28),
+    ],
+    &[
+        (0, 13),
+        (1, 12),
+        (3, 14),
+        (4, 8),
+        (5, 6),
+        (7, 11),
+        (9, 10),
+        (0, 5),
+        (1, 7),
+        (2, 9),
+        (3, 4),
+        (6, 13),
+        (8, 14),
+        (11, 12),
+        (0, 1),
+        (2, 3),
+        (4, 5),
+        (6, 8),
+        (7, 9),
+        (10, 11),
+        (12, 13),
+        (0, 2),
+        (1, 3),
+        (4, 10),
+        (5, 11),
+        (6, 7),
+        (8, 9),
+        (12, 14),
+        (1, 2),
+        (3, 12),
+        (4, 6),
+        (5, 7),
+        (8, 10),
+        (9, 11),
+        (13, 14),
+        (1, 4),
+        (2, 6),
+        (5, 8),
+        (7, 10),
+        (9, 13),
+        (11, 14),
+        (2, 4),
+        (3, 6),
+        (9, 12),
+        (11, 13),
+        (3, 5),
+        (6, 8),
+        (7, 9),
+        (10, 12),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (9, 10),
+        (11, 12),
+        (6, 7),
+        (8, 9),
+        (15, 28),
+        (16, 27),
+        (18, 29),
+        (19, 23),
+        (20, 21),
+        (22, 26),
+        (24, 25),
+        (15, 20),
+        (16, 22),
+        (17, 24),
+        (18, 19),
+        (21, 28),
+        (23, 29),
+        (26, 27),
+        (15, 16),
+        (17, 18),
+        (19, 20),
+        (21, 23),
+        (22, 24),
+        (25, 26),
+        (27, 28),
+        (15, 17),
+        (16, 18),
+        (19, 25),
+        (20, 26),
+        (21, 22),
+        (23, 24),
+        (27, 29),
+        (16, 17),
+        (18, 27),
+        (19, 21),
+        (20, 22),
+        (23, 25),
+        (24, 26),
+        (28, 29),
+        (16, 19),
+        (17, 21),
+        (20, 23),
+        (22, 25),
+        (24, 28),
+        (26, 29),
+        (17, 19),
+        (18, 21),
+        (24, 27),
+        (26, 28),
+        (18, 20),
+        (21, 23),
+        (22, 24),
+        (25, 27),
+        (18, 19),
+        (20, 21),
+        (22, 23),
+        (24, 25),
+        (26, 27),
+        (21, 22),
+        (23, 24),
+        (0, 15),
+        (8, 23),
+        (8, 15),
+        (4, 19),
+        (12, 27),
+        (12, 19),
+        (4, 8),
+        (12, 15),
+        (19, 23),
+        (2, 17),
+        (10, 25),
+        (10, 17),
+        (6, 21),
+        (14, 29),
+        (14, 21),
+        (6, 10),
+        (14, 17),
+        (21, 25),
+        (2, 4),
+        (6, 8),
+        (10, 12),
+        (14, 15),
+        (17, 19),
+        (21, 23),
+        (25, 27),
+        (1, 16),
+        (9, 24),
+        (9, 16),
+        (5, 20),
+        (13, 28),
+        (13, 20),
+        (5, 9),
+        (13, 16),
+        (20, 24),
+        (3, 18),
+        (11, 26),
+        (11, 18),
+        (7, 22),
+        (7, 11),
+        (18, 22),
+        (3, 5),
+        (7, 9),
+        (11, 13),
+        (16, 18),
+        (20, 22),
+        (24, 26),
+        (1, 2),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (9, 10),
+        (11, 12),
+        (13, 14),
+        (15, 16),
+        (17, 18),
+        (19, 20),
+        (21, 22),
+        (23, 24),
+        (25, 26),
+        (27, 28),
+    ],
+    &[
+        (0, 13),
+        (1, 12),
+        (3, 14),
+        (4, 8),
+        (5, 6),
+        (7, 11),
+        (9, 10),
+        (0, 5),
+        (1, 7),
+        (2, 9),
+        (3, 4),
+        (6, 13),
+        (8, 14),
+        (11, 12),
+        (0, 1),
+        (2, 3),
+        (4, 5),
+        (6, 8),
+        (7, 9),
+        (10, 11),
+        (12, 13),
+        (0, 2),
+        (1, 3),
+        (4, 10),
+        (5, 11),
+        (6, 7),
+        (8, 9),
+        (12, 14),
+        (1, 2),
+        (3, 12),
+        (4, 6),
+        (5, 7),
+        (8, 10),
+        (9, 11),
+        (13, 14),
+        (1, 4),
+        (2, 6),
+        (5, 8),
+        (7, 10),
+        (9, 13),
+        (11, 14),
+        (2, 4),
+        (3, 6),
+        (9, 12),
+        (11, 13),
+        (3, 5),
+        (6, 8),
+        (7, 9),
+        (10, 12),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (9, 10),
+        (11, 12),
+        (6, 7),
+        (8, 9),
+        (15, 28),
+        (16, 27),
+        (17, 30),
+        (18, 29),
+        (19, 23),
+        (20, 21),
+        (22, 26),
+        (24, 25),
+        (15, 20),
+        (16, 22),
+        (17, 24),
+        (18, 19),
+        (21, 28),
+        (23, 29),
+        (25, 30),
+        (26, 27),
+        (15, 16),
+        (17, 18),
+        (19, 20),
+        (21, 23),
+        (22, 24),
+        (25, 26),
+        (27, 28),
+        (29, 30),
+        (15, 17),
+        (16, 18),
+        (19, 25),
+        (20, 26),
+        (21, 22),
+        (23, 24),
+        (27, 29),
+        (28, 30),
+        (16, 17),
+        (18, 27),
+        (19, 21),
+        (20, 22),
+        (23, 25),
+        (24, 26),
+        (28, 29),
+        (16, 19),
+        (17, 21),
+        (20, 23),
+        (22, 25),
+        (24, 28),
+        (26, 29),
+        (17, 19),
+        (18, 21),
+        (24, 27),
+        (26, 28),
+        (18, 20),
+        (21, 23),
+        (22, 24),
+        (25, 27),
+        (18, 19),
+        (20, 21),
+        (22, 23),
+        (24, 25),
+        (26, 27),
+        (21, 22),
+        (23, 24),
+        (0, 15),
+        (8, 23),
+        (8, 15),
+        (4, 19),
+        (12, 27),
+        (12, 19),
+        (4, 8),
+        (12, 15),
+        (19, 23),
+        (2, 17),
+        (10, 25),
+        (10, 17),
+        (6, 21),
+        (14, 29),
+        (14, 21),
+        (6, 10),
+        (14, 17),
+        (21, 25),
+        (2, 4),
+        (6, 8),
+        (10, 12),
+        (14, 15),
+        (17, 19),
+        (21, 23),
+        (25, 27),
+        (1, 16),
+        (9, 24),
+        (9, 16),
+        (5, 20),
+        (13, 28),
+        (13, 20),
+        (5, 9),
+        (13, 16),
+        (20, 24),
+        (3, 18),
+        (11, 26),
+        (11, 18),
+        (7, 22),
+        (22, 30),
+        (7, 11),
+        (18, 22),
+        (26, 30),
+        (3, 5),
+        (7, 9),
+        (11, 13),
+        (16, 18),
+        (20, 22),
+        (24, 26),
+        (28, 30),
+        (1, 2),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (9, 10),
+        (11, 12),
+        (13, 14),
+        (15, 16),
+        (17, 18),
+        (19, 20),
+        (21, 22),
+        (23, 24),
+        (25, 26),
+        (27, 28),
+        (29, 30),
+    ],
+    &[
+        (0, 13),
+        (1, 12),
+        (2, 15),
+        (3, 14),
+        (4, 8),
+        (5, 6),
+        (7, 11),
+        (9, 10),
+        (0, 5),
+        (1, 7),
+        (2, 9),
+        (3, 4),
+        (6, 13),
+        (8, 14),
+        (10, 15),
+        (11, 12),
+        (0, 1),
+        (2, 3),
+        (4, 5),
+        (6, 8),
+        (7, 9),
+        (10, 11),
+        (12, 13),
+        (14, 15),
+        (0, 2),
+        (1, 3),
+        (4, 10),
+        (5, 11),
+        (6, 7),
+        (8, 9),
+        (12, 14),
+        (13, 15),
+        (1, 2),
+        (3, 12),
+        (4, 6),
+        (5, 7),
+        (8, 10),
+        (9, 11),
+        (13, 14),
+        (1, 4),
+        (2, 6),
+        (5, 8),
+        (7, 10),
+        (9, 13),
+        (11, 14),
+        (2, 4),
+        (3, 6),
+        (9, 12),
+        (11, 13),
+        (3, 5),
+        (6, 8),
+        (7, 9),
+        (10, 12),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (9, 10),
+        (11, 12),
+        (6, 7),
+        (8, 9),
+        (16, 29),
+        (17, 28),
+        (18, 31),
+        (19, 30),
+        (20, 24),
+        (21, 22),
+        (23, 27),
+        (25, 26),
+        (16, 21),
+        (17, 23),
+        (18, 25),
+        (19, 20),
+        (22, 29),
+        (24, 30),
+        (26, 31),
+        (27, 28),
+        (16, 17),
+        (18, 19),
+        (20, 21),
+        (22, 24),
+        (23, 25),
+        (26, 27),
+        (28, 29),
+        (30, 31),
+        (16, 18),
+        (17, 19),
+        (20, 26),
+        (21, 27),
+        (22, 23),
+        (24, 25),
+        (28, 30),
+        (29, 31),
+        (17, 18),
+        (19, 28),
+        (20, 22),
+        (21, 23),
+        (24, 26),
+        (25, 27),
+        (29, 30),
+        (17, 20),
+        (18, 22),
+        (21, 24),
+        (23, 26),
+        (25, 29),
+        (27, 30),
+        (18, 20),
+        (19, 22),
+        (25, 28),
+        (27, 29),
+        (19, 21),
+        (22, 24),
+        (23, 25),
+        (26, 28),
+        (19, 20),
+        (21, 22),
+        (23, 24),
+        (25, 26),
+        (27, 28),
+        (22, 23),
+        (24, 25),
+        (0, 16),
+        (8, 24),
+        (8, 16),
+        (4, 20),
+        (12, 28),
+        (12, 20),
+        (4, 8),
+        (12, 16),
+        (20, 24),
+        (2, 18),
+        (10, 26),
+        (10, 18),
+        (6, 22),
+        (14, 30),
+        (14, 22),
+        (6, 10),
+        (14, 18),
+        (22, 26),
+        (2, 4),
+        (6, 8),
+        (10, 12),
+        (14, 16),
+        (18, 20),
+        (22, 24),
+        (26, 28),
+        (1, 17),
+        (9, 25),
+        (9, 17),
+        (5, 21),
+        (13, 29),
+        (13, 21),
+        (5, 9),
+        (13, 17),
+        (21, 25),
+        (3, 19),
+        (11, 27),
+        (11, 19),
+        (7, 23),
+        (15, 31),
+        (15, 23),
+        (7, 11),
+        (15, 19),
+        (23, 27),
+        (3, 5),
+        (7, 9),
+        (11, 13),
+        (15, 17),
+        (19, 21),
+        (23, 25),
+        (27, 29),
+        (1, 2),
+        (3, 4),
+        (5, 6),
+        (7, 8),
+        (9, 10),
+        (11, 12),
+        (13, 14),
+        (15, 16),
+        (17, 18),
+        (19, 20),
+        (21, 22),
+        (23, 24),
+        (25, 26),
+        (27, 28),
+        (29, 30),
+    ],
+];
+
+/// The comparators of a network, which are fixed in size so they can be computed at compile time
+struct Comparators {
+    pairs: [(usize, usize); MAX_COMPARATORS],
+    len: usize,
+}
+
+/// The sorting network for `N` elements, computed once per `N` at compile time
+struct Network<const N: usize>;
+
+impl<const N: usize> Network<N> {
+    const COMPARATORS: Comparators = network_comparators(N);
+}
+
+/// Sorts an array of up to 32 elements with a sorting network. The comparators only depend on `N`, so the
+/// compiler can unroll them completely, which makes this faster than a general sort in hot loops.
+/// Up to 16 elements the networks with the fewest known comparators are used, above that networks close
+/// to them, see `BEST_KNOWN_NETWORKS`. Not stable. Using it with more than 32 elements fails to compile.
+pub fn sort_network<T: PartialOrd, const N: usize>(elements: &mut [T; N]) {
+    sort_network_impl(elements, &mut |a: &T, b: &T| a < b);
+}
+
+/// Like `sort_network`, but with a comparator function
+pub fn sort_network_by<T, F: FnMut(&T, &T) -> Ordering, const N: usize>(
+    elements: &mut [T; N],
+    mut compare: F,
+) {
+    sort_network_impl(elements, &mut |a: &T, b: &T| {
+        compare(a, b) == Ordering::Less
+    });
+}
+
+fn sort_network_impl<T, F: FnMut(&T, &T) -> bool, const N: usize>(
+    elements: &mut [T; N],
+    is_less: &mut F,
+) {
+    let comparators = &Network::<N>::COMPARATORS;
+    for &(a, b) in &comparators.pairs[..comparators.len] {
+        compare_exchange(elements, a, b, is_less);
+    }
+}
+
+const fn network_comparators(n: usize) -> Comparators {
+    assert!(
+        n <= MAX_NETWORK_SIZE,
+        "sorting networks support up to 32 elements"
+    );
+    let mut comparators = Comparators {
+        pairs: [(0, 0); MAX_COMPARATORS],
+        len: 0,
+    };
+    let network = BEST_KNOWN_NETWORKS[n];
+    while comparators.len < network.len() {
+        comparators.pairs[comparators.len] = network[comparators.len];
+        comparators.len += 1;
+    }
+    comparators
+}
+
+/// Sorts the elements with Batcher's odd-even merge sort network. Makes O(n log² n) comparisons, which
+/// do not depend on the elements, so the sort runs in the same time for every input of a length.
+/// Not stable. Panics if the length is not a power of two.
+pub fn odd_even_merge_sort<T: PartialOrd>(elements: &mut [T]) {
+    odd_even_merge_sort_impl(elements, &mut |a: &T, b: &T| a < b);
+}
+
+/// Like `odd_even_merge_sort`, but with a comparator function
+pub fn odd_even_merge_sort_by<T, F: FnMut(&T, &T) -> Ordering>(elements: &mut [T], mut compare: F) {
+    odd_even_merge_sort_impl(elements, &mut |a: &T, b: &T| {
+        compare(a, b) == Ordering::Less
+    });
+}
+
+fn odd_even_merge_sort_impl<T, F: FnMut(&T, &T) -> bool>(elements: &mut [T], is_less: &mut F) {
+    let n = elements.len();
+    assert!(
+        n == 0 || n.is_power_of_two(),
+        "the length has to be a power of two"
+    );
+    // sorted runs of length `run` are merged pairwise, each merge compares elements `step` apart within
+    // blocks of `2 * step`, skipping the pairs that would cross two merged runs
+    let mut run = 1;
+    while run < n {
+        let mut step = run;
+        while step > 0 {
+            let offset = step % run;
+            let mut block = offset;
+            while block + step < n {
+                for i in 0..step.min(n - block - step) {
+                    if (block + i) / (2 * run) == (block + i + step) / (2 * run) {
+                        compare_exchange(elements, block + i, block + i + step, is_less);
+                    }
+                }
+                block += 2 * step;
+            }
+            step /= 2;
+        }
+        run *= 2;
+    }
+}
+
+/// Sorts the elements with a bitonic sorting network. Makes slightly more comparisons than
+/// `odd_even_merge_sort`, but always compares elements exactly `2^k` apart, which suits SIMD and GPUs.
+/// Not stable. Panics if the length is not a power of two.
+pub fn bitonic_sort<T: PartialOrd>(elements: &mut [T]) {
+    bitonic_sort_impl(elements, &mut |a: &T, b: &T| a < b);
+}
+
+/// Like `bitonic_sort`, but with a comparator function
+pub fn bitonic_sort_by<T, F: FnMut(&T, &T) -> Ordering>(elements: &mut [T], mut compare: F) {
+    bitonic_sort_impl(elements, &mut |a: &T, b: &T| {
+        compare(a, b) == Ordering::Less
+    });
+}
+
+fn bitonic_sort_impl<T, F: FnMut(&T, &T) -> bool>(elements: &mut [T], is_less: &mut F) {
+    let n = elements.len();
+    assert!(
+        n == 0 || n.is_power_of_two(),
+        "the length has to be a power of two"
+    );
+    // blocks of length `block` are sorted alternately ascending and descending, so that every pair of
+    // neighbouring blocks forms a bitonic sequence that the next level merges
+    let mut block = 2;
+    while block <= n {
+        let mut step = block / 2;
+        while step > 0 {
+            for i in 0..n {
+                let partner = i ^ step;
+                if partner > i {
+                    if i & block == 0 {
+                        compare_exchange(elements, i, partner, is_less);
+                    } else {
+                        compare_exchange(elements, partner, i, is_less);
+                    }
+                }
+            }
+            step /= 2;
+        }
+        block *= 2;
+    }
+}
+
+/// Swaps the elements at `a` and `b` if the one at `b` is less
+#[inline(always)]
+fn compare_exchange<T, F: FnMut(&T, &T) -> bool>(
+    elements: &mut [T],
+    a: usize,
+    b: usize,
+    is_less: &mut F,
+) {
+    if is_less(&elements[b], &elements[a]) {
+        elements.swap(a, b);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use crate::sorting::test_helpers::{self, is_sorted};
+
+    use super::*;
+
+    /// Checks a network with the 0-1 principle: a comparator network sorts every input if it sorts
+    /// every input of zeros and ones
+    fn verify_zero_one_principle(len: usize, mut sort: impl FnMut(&mut [u8])) {
+        let mut bits = vec![0; len];
+        for pattern in 0..1u64 << len {
+            for (i, bit) in bits.iter_mut().enumerate() {
+                *bit = (pattern >> i & 1) as u8;
+            }
+            sort(&mut bits);
+            assert!(
+                is_sorted(bits.iter()),
+                "length {} does not sort pattern {:b}",
+                len,
+                pattern
+            );
+        }
+    }
+
+    macro_rules! verify_networks {
+        ($($n:literal),*) => {
+            $(verify_zero_one_principle($n, |bits| {
+                sort_network::<u8, $n>(bits.try_into().unwrap())
+            });)*
+        };
+    }
+
+    macro_rules! sort_random_arrays {
+        ($($n:literal),*) => {
+            let mut generator = rand::thread_rng();
+            $(for _ in 0..100 {
+                let mut items: [i32; $n] = generator.gen();
+                sort_network(&mut items);
+                assert!(is_sorted(items.iter()));
+            })*
+        };
+    }
+
+    /// Checks a network with the 0-1 principle without going through all 2^len inputs. Before any
+    /// comparator connects them, groups of wires take their outputs independently of each other, so each
+    /// group keeps the set of outputs it can have, as bits by wire. A comparator joining two groups combines
+    /// every output of one with every output of the other, which stays small because the networks sort
+    /// parts of the elements before merging them.
+    fn verify_zero_one_outputs(len: usize, comparators: &[(usize, usize)]) {
+        let mut group: Vec<usize> = (0..len).collect();
+        let mut outputs: Vec<Vec<u64>> = (0..len).map(|wire| vec![0, 1 << wire]).collect();
+        let join = |group: &mut Vec<usize>, outputs: &mut Vec<Vec<u64>>, a: usize, b: usize| {
+            let (kept, joined) = (group[a], group[b]);
+            if kept != joined {
+                let combined = outputs[kept]
+                    .iter()
+                    .flat_map(|&x| outputs[joined].iter().map(move |&y| x | y))
+                    .collect();
+                outputs[kept] = combined;
+                outputs[joined] = Vec::new();
+                group
+                    .iter_mut()
+                    .filter(|g| **g == joined)
+                    .for_each(|g| *g = kept);
+            }
+            kept
+        };
+        for &(a, b) in comparators {
+            let kept = join(&mut group, &mut outputs, a, b);
+            let (bit_a, bit_b) = (1 << a, 1 << b);
+            for output in &mut outputs[kept] {
+                if *output & bit_a != 0 && *output & bit_b == 0 {
+                    *output ^= bit_a | bit_b;
+                }
+            }
+            outputs[kept].sort_unstable();
+            outputs[kept].dedup();
+        }
+        for wire in 1..len {
+            join(&mut group, &mut outputs, 0, wire);
+        }
+        for &output in outputs.iter().flatten() {
+            // a one in front of a zero
+            assert_eq!(
+                output & !(output >> 1) & ((1 << len.saturating_sub(1)) - 1),
+                0,
+                "length {} does not sort output {:b}",
+                len,
+                output
+            );
+        }
+    }
+
+    #[test]
+    fn test_sort_network_zero_one_principle() {
+        verify_networks!(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16);
+    }
+
+    #[test]
+    fn test_all_networks_zero_one_principle() {
+        for (n, network) in BEST_KNOWN_NETWORKS.iter().enumerate() {
+            verify_zero_one_outputs(n, network);
+        }
+    }
+
+    #[test]
+    fn test_sort_network_larger_sizes() {
+        sort_random_arrays!(17, 20, 23, 24, 29, 31, 32);
+    }
+
+    #[test]
+    fn test_network_sizes() {
+        let best_known = [
+            0, 0, 1, 3, 5, 9, 12, 16, 19, 25, 29, 35, 39, 45, 51, 56, 60, 71, 77, 85, 91, 100, 107,
+            115, 120, 132, 139, 150, 155, 165, 172, 180, 185,
+        ];
+        let sizes: Vec<usize> = BEST_KNOWN_NETWORKS
+            .iter()
+            .map(|network| network.len())
+            .collect();
+        let above_best_known: Vec<(usize, usize)> = (0..=MAX_NETWORK_SIZE)
+            .filter(|&n| sizes[n] != best_known[n])
+            .map(|n| (n, sizes[n] - best_known[n]))
+            .collect();
+        assert_eq!(
+            above_best_known,
+            [
+                (17, 2),
+                (18, 3),
+                (19, 3),
+                (20, 2),
+                (21, 3),
+                (22, 3),
+                (23, 3),
+                (24, 3),
+                (25, 1),
+                (26, 1),
+                (28, 1)
+            ]
+        );
+        for (n, network) in BEST_KNOWN_NETWORKS.iter().enumerate() {
+            assert!(network.iter().all(|&(a, b)| a < b && b < n));
+        }
+
+        let sizes = [
+            Network::<16>::COMPARATORS.len,
+            Network::<17>::COMPARATORS.len,
+            Network::<32>::COMPARATORS.len,
+        ];
+        assert_eq!(sizes, [60, 73, MAX_COMPARATORS]);
+    }
+
+    #[test]
+    fn test_sort_network_by() {
+        let mut items = [3, 1, 4, 1, 5, 9, 2, 6, 5, 3];
+        sort_network_by(&mut items, |a, b| b.cmp(a));
+        assert_eq!(items, [9, 6, 5, 5, 4, 3, 3, 2, 1, 1]);
+    }
+
+    #[test]
+    fn test_sort_network_keeps_all_elements_with_nans() {
+        let mut items = [2.0, f64::NAN, 1.0, f64::NAN, 0.0, 3.0];
+        sort_network(&mut items);
+        assert_eq!(items.iter().filter(|item| item.is_nan()).count(), 2);
+    }
+
+    #[test]
+    fn test_power_of_two_networks_zero_one_principle() {
+        for len in [0, 1, 2, 4, 8, 16] {
+            verify_zero_one_principle(len, odd_even_merge_sort);
+            verify_zero_one_principle(len, bitonic_sort);
+        }
+    }
+
+    #[test]
+    fn test_power_of_two_networks_with_random_items() {
+        let mut generator = rand::thread_rng();
+        for len in [32, 64, 1024] {
+            let items: Vec<i64> = (0..len).map(|_| generator.gen_range(-100..100)).collect();
+            let mut merged = items.clone();
+            odd_even_merge_sort(&mut merged);
+            assert!(is_sorted(merged.iter()));
+            let mut bitonic = items;
+            bitonic_sort_by(&mut bitonic, |a, b| b.cmp(a));
+            assert!(is_sorted(bitonic.iter().rev()));
+        }
+    }
+
+    #[test]
+    fn test_power_of_two_networks_are_panic_safe() {
+        test_helpers::test_panic_safety(odd_even_merge_sort, 32);
+        test_helpers::test_panic_safety(bitonic_sort, 32);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_odd_even_merge_sort_rejects_other_lengths() {
+        odd_even_merge_sort(&mut [3, 2, 1]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bitonic_sort_rejects_other_lengths() {
+        bitonic_sort(&mut [3, 2, 1]);
+    }
+}
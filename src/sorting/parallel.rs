@@ -0,0 +1,255 @@
+use std::cmp::Ordering;
+use std::thread;
+
+use super::instrumented::NoProbe;
+use super::mergesort::{merge, mergesort_impl};
+use super::pivot::MedianOfThree;
+use super::quicksort::{partition_and_split, quicksort_impl};
+
+/// Slices of at most this length are sorted on the current thread by default
+const DEFAULT_SEQUENTIAL_CUTOFF: usize = 1 << 13;
+
+/// Controls how the parallel sorts split their work
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct ParallelConfig {
+    /// The maximum number of threads working at the same time, including the calling thread.
+    /// A value of 0 is treated like 1.
+    pub threads: usize,
+    /// Slices of at most this length are not split any further and sorted sequentially
+    pub sequential_cutoff: usize,
+}
+
+impl Default for ParallelConfig {
+    /// Uses as many threads as the machine offers
+    fn default() -> Self {
+        ParallelConfig {
+            threads: thread::available_parallelism().map_or(1, |threads| threads.get()),
+            sequential_cutoff: DEFAULT_SEQUENTIAL_CUTOFF,
+        }
+    }
+}
+
+/// Sorts the elements with quicksort, handing the smaller part of every partition to another thread while
+/// threads are left. The elements end up in exactly the same order as with `quicksort`.
+pub fn par_quicksort<T: PartialOrd + Send>(elements: &mut [T], config: ParallelConfig) {
+    par_quicksort_impl(
+        elements,
+        &|a: &T, b: &T| a < b,
+        config.threads,
+        config.sequential_cutoff,
+    );
+}
+
+/// Like `par_quicksort`, but with a comparator function
+pub fn par_quicksort_by<T: Send, F: Fn(&T, &T) -> Ordering + Sync>(
+    elements: &mut [T],
+    config: ParallelConfig,
+    compare: F,
+) {
+    par_quicksort_impl(
+        elements,
+        &|a: &T, b: &T| compare(a, b) == Ordering::Less,
+        config.threads,
+        config.sequential_cutoff,
+    );
+}
+
+/// Sorts the elements stabelly with a merge sort that sorts both halves on different threads while
+/// threads are left. The elements end up in exactly the same order as with `mergesort`.
+pub fn par_mergesort<T: PartialOrd + Send>(elements: &mut [T], config: ParallelConfig) {
+    par_mergesort_impl(
+        elements,
+        &|a: &T, b: &T| a < b,
+        config.threads,
+        config.sequential_cutoff,
+    );
+}
+
+/// Like `par_mergesort`, but with a comparator function
+pub fn par_mergesort_by<T: Send, F: Fn(&T, &T) -> Ordering + Sync>(
+    elements: &mut [T],
+    config: ParallelConfig,
+    compare: F,
+) {
+    par_mergesort_impl(
+        elements,
+        &|a: &T, b: &T| compare(a, b) == Ordering::Less,
+        config.threads,
+        config.sequential_cutoff,
+    );
+}
+
+fn par_quicksort_impl<T: Send, F: Fn(&T, &T) -> bool + Sync>(
+    mut elements: &mut [T],
+    is_less: &F,
+    mut threads: usize,
+    cutoff: usize,
+) {
+    thread::scope(move |scope| {
+        // partitions the same way quicksort_impl does, so the result does not depend on the threads
+        while threads > 1 && elements.len() > cutoff {
+            let (smaller, larger) =
+                partition_and_split(elements, &mut &*is_less, &mut MedianOfThree, &mut NoProbe);
+            let spawned_threads = threads / 2;
+            scope.spawn(move || par_quicksort_impl(smaller, is_less, spawned_threads, cutoff));
+            threads -= spawned_threads;
+            elements = larger;
+        }
+        quicksort_impl(elements, &mut &*is_less, &mut MedianOfThree, &mut NoProbe);
+    });
+}
+
+fn par_mergesort_impl<T: Send, F: Fn(&T, &T) -> bool + Sync>(
+    elements: &mut [T],
+    is_less: &F,
+    threads: usize,
+    cutoff: usize,
+) {
+    if threads <= 1 || elements.len() <= cutoff {
+        mergesort_impl(elements, &mut &*is_less, &mut NoProbe);
+        return;
+    }
+
+    // the same split as in the sequential merge sort
+    let mid = elements.len() / 2;
+    let (left, right) = elements.split_at_mut(mid);
+    let spawned_threads = threads / 2;
+    thread::scope(|scope| {
+        scope.spawn(|| par_mergesort_impl(left, is_less, spawned_threads, cutoff));
+        par_mergesort_impl(right, is_less, threads - spawned_threads, cutoff);
+    });
+    let mut buffer = Vec::with_capacity(elements.len() - mid);
+    merge(elements, mid, &mut buffer, &mut &*is_less, &mut NoProbe);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sorting::test_helpers::{
+        self, is_stabelly_sorted, random_comparable_list, StableSortComparableItem,
+    };
+    use crate::sorting::{mergesort_by, quicksort_by};
+
+    use super::*;
+
+    fn small_chunks(threads: usize) -> ParallelConfig {
+        ParallelConfig {
+            threads,
+            sequential_cutoff: 64,
+        }
+    }
+
+    fn initial_indices(items: &[StableSortComparableItem]) -> Vec<usize> {
+        items.iter().map(|item| item.initial_index()).collect()
+    }
+
+    fn default_par_quicksort<T: PartialOrd + Send>(elements: &mut [T]) {
+        par_quicksort(elements, ParallelConfig::default());
+    }
+
+    fn default_par_mergesort<T: PartialOrd + Send>(elements: &mut [T]) {
+        par_mergesort(elements, ParallelConfig::default());
+    }
+
+    mod quicksort_basics {
+        use super::*;
+        test_helpers::basic_sorting_tests!(default_par_quicksort);
+    }
+
+    mod mergesort_basics {
+        use super::*;
+        test_helpers::basic_sorting_tests!(default_par_mergesort);
+    }
+
+    #[test]
+    fn test_par_quicksort_matches_quicksort() {
+        for threads in [0, 1, 2, 3, 4, 8, 13] {
+            let data = random_comparable_list(20_000, -100, 100);
+            let mut expected = data.clone();
+            quicksort_by(&mut expected, |a, b| a.value().cmp(&b.value()));
+            let mut sorted = data;
+            par_quicksort_by(&mut sorted, small_chunks(threads), |a, b| {
+                a.value().cmp(&b.value())
+            });
+            // quicksort is not stable, but the parallel version has to reorder equal elements the same way
+            assert_eq!(
+                initial_indices(&sorted),
+                initial_indices(&expected),
+                "{} threads",
+                threads
+            );
+        }
+    }
+
+    #[test]
+    fn test_par_mergesort_matches_mergesort() {
+        for threads in [0, 1, 2, 3, 4, 8, 13] {
+            let data = random_comparable_list(20_000, -100, 100);
+            let mut expected = data.clone();
+            mergesort_by(&mut expected, |a, b| a.value().cmp(&b.value()));
+            let mut sorted = data;
+            par_mergesort_by(&mut sorted, small_chunks(threads), |a, b| {
+                a.value().cmp(&b.value())
+            });
+            assert!(is_stabelly_sorted(&sorted));
+            assert_eq!(
+                initial_indices(&sorted),
+                initial_indices(&expected),
+                "{} threads",
+                threads
+            );
+        }
+    }
+
+    #[test]
+    fn test_parallel_sorts_on_integers() {
+        for threads in [1, 2, 4, 7] {
+            test_helpers::test_unstable_sort(
+                &|items: &mut [i32]| par_quicksort(items, small_chunks(threads)),
+                10_000,
+                -1000,
+                1000,
+            );
+            test_helpers::test_unstable_sort(
+                &|items: &mut [i32]| par_mergesort(items, small_chunks(threads)),
+                10_000,
+                -1000,
+                1000,
+            );
+            let mut descending: Vec<_> = (0..10_000).rev().collect();
+            par_quicksort(&mut descending, small_chunks(threads));
+            assert!(descending.iter().copied().eq(0..10_000));
+        }
+    }
+
+    #[test]
+    fn test_parallel_sorts_below_the_cutoff() {
+        let config = ParallelConfig {
+            threads: 8,
+            sequential_cutoff: 1000,
+        };
+        let mut items = vec![5, 3, 9, 1, 1, 0];
+        par_quicksort(&mut items, config);
+        assert_eq!(items, [0, 1, 1, 3, 5, 9]);
+        let mut items = vec![5, 3, 9, 1, 1, 0];
+        par_mergesort(&mut items, config);
+        assert_eq!(items, [0, 1, 1, 3, 5, 9]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_panics_on_worker_threads_are_propagated() {
+        let mut items: Vec<_> = (0..10_000).rev().collect();
+        par_mergesort_by(&mut items, small_chunks(4), |a: &i32, b| {
+            if *a == 42 {
+                panic!("comparison failed");
+            }
+            a.cmp(b)
+        });
+    }
+
+    #[test]
+    fn test_parallel_sorts_conformance() {
+        test_helpers::test_sort_conformance(|items: &mut [i64]| par_quicksort(items, small_chunks(4)));
+        test_helpers::test_stable_sort_conformance(|items| par_mergesort(items, small_chunks(4)));
+    }
+}
@@ -0,0 +1,263 @@
+use std::cmp::Ordering;
+
+use super::{bubblesort_by, bubblesort_by_key, quicksort_by};
+
+/// Decides where `quicksort_partial` and `bubblesort_partial` put elements that are not comparable to
+/// themselves, like `f64::NAN`
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum NanPolicy {
+    First,
+    Last,
+    Error,
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum PartialOrderError {
+    /// Two elements could not be compared. If it was found while sorting, the slice is left as some
+    /// permutation of the input.
+    Incomparable,
+}
+
+/// Floating point types that can be ordered completely with the IEEE 754 totalOrder predicate
+pub trait TotalOrder {
+    fn total_cmp(&self, other: &Self) -> Ordering;
+}
+
+impl TotalOrder for f32 {
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f32::total_cmp(self, other)
+    }
+}
+
+impl TotalOrder for f64 {
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f64::total_cmp(self, other)
+    }
+}
+
+/// Sorts floats with quicksort by their total order:
+/// negative NaNs, -inf, negative numbers, -0.0, 0.0, positive numbers, inf, positive NaNs
+pub fn quicksort_floats<T: TotalOrder>(elements: &mut [T]) {
+    quicksort_by(elements, T::total_cmp);
+}
+
+/// Sorts floats stabelly with bubblesort by their total order, see `quicksort_floats`
+pub fn bubblesort_floats<T: TotalOrder>(elements: &mut [T]) {
+    bubblesort_by(elements, T::total_cmp);
+}
+
+/// Sorts a partially ordered slice with quicksort. Elements that are not comparable to themselves are
+/// moved to the front or the back, depending on `policy`. With `NanPolicy::Error` the slice is left
+/// untouched if it contains such an element.
+/// Other pairs that cannot be compared are treated as equal, unless the policy is `NanPolicy::Error`.
+pub fn quicksort_partial<T: PartialOrd>(
+    elements: &mut [T],
+    policy: NanPolicy,
+) -> Result<(), PartialOrderError> {
+    let comparable = match policy {
+        NanPolicy::First => {
+            let nans = partition_nans_first(elements);
+            &mut elements[nans..]
+        }
+        NanPolicy::Last => {
+            let nans = partition_nans_first(elements);
+            elements.rotate_left(nans);
+            let len = elements.len();
+            &mut elements[..len - nans]
+        }
+        NanPolicy::Error => {
+            check_for_nans(elements)?;
+            elements
+        }
+    };
+    let mut found_incomparable = false;
+    quicksort_by(comparable, |a, b| compare(a, b, &mut found_incomparable));
+    finish(policy, found_incomparable)
+}
+
+/// Sorts a partially ordered slice stabelly with bubblesort, see `quicksort_partial`.
+/// The elements that are not comparable to themselves keep their relative order as well.
+pub fn bubblesort_partial<T: PartialOrd>(
+    elements: &mut [T],
+    policy: NanPolicy,
+) -> Result<(), PartialOrderError> {
+    match policy {
+        NanPolicy::First => bubblesort_by_key(elements, |element| !is_nan(element)),
+        NanPolicy::Last => bubblesort_by_key(elements, is_nan),
+        NanPolicy::Error => check_for_nans(elements)?,
+    }
+    let mut found_incomparable = false;
+    bubblesort_by(elements, |a, b| {
+        if is_nan(a) || is_nan(b) {
+            // keeps the nans where they have been moved to
+            Ordering::Equal
+        } else {
+            compare(a, b, &mut found_incomparable)
+        }
+    });
+    finish(policy, found_incomparable)
+}
+
+fn is_nan<T: PartialOrd>(element: &T) -> bool {
+    element.partial_cmp(element).is_none()
+}
+
+fn compare<T: PartialOrd>(a: &T, b: &T, found_incomparable: &mut bool) -> Ordering {
+    a.partial_cmp(b).unwrap_or_else(|| {
+        *found_incomparable = true;
+        Ordering::Equal
+    })
+}
+
+fn check_for_nans<T: PartialOrd>(elements: &[T]) -> Result<(), PartialOrderError> {
+    if elements.iter().any(is_nan) {
+        return Err(PartialOrderError::Incomparable);
+    }
+    Ok(())
+}
+
+fn finish(policy: NanPolicy, found_incomparable: bool) -> Result<(), PartialOrderError> {
+    if policy == NanPolicy::Error && found_incomparable {
+        return Err(PartialOrderError::Incomparable);
+    }
+    Ok(())
+}
+
+/// Moves all nans to the front and returns how many there are
+fn partition_nans_first<T: PartialOrd>(elements: &mut [T]) -> usize {
+    let mut nans = 0;
+    for i in 0..elements.len() {
+        if is_nan(&elements[i]) {
+            elements.swap(nans, i);
+            nans += 1;
+        }
+    }
+    nans
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sorting::test_helpers::test_panic_safety;
+
+    use super::*;
+
+    fn with_nans() -> Vec<f64> {
+        vec![3.0, f64::NAN, -1.0, 2.5, f64::NAN, 0.0, -7.0, f64::INFINITY, f64::NAN, 1.0]
+    }
+
+    const SORTED: [f64; 7] = [-7.0, -1.0, 0.0, 1.0, 2.5, 3.0, f64::INFINITY];
+
+    #[test]
+    fn test_quicksort_floats_uses_total_order() {
+        let mut items = vec![0.0, -f64::NAN, 1.0, f64::NAN, -0.0, f64::NEG_INFINITY, f64::INFINITY];
+        quicksort_floats(&mut items);
+        assert!(items[0].is_nan() && items[0].is_sign_negative());
+        assert_eq!(&items[1..6], &[f64::NEG_INFINITY, -0.0, 0.0, 1.0, f64::INFINITY]);
+        assert!(items[1..6].windows(2).all(|pair| pair[0].total_cmp(&pair[1]).is_lt()));
+        assert!(items[6].is_nan() && items[6].is_sign_positive());
+    }
+
+    #[test]
+    fn test_bubblesort_floats_uses_total_order() {
+        let mut items: Vec<f32> = vec![2.0, f32::NAN, -1.0, 0.5];
+        bubblesort_floats(&mut items);
+        assert_eq!(&items[..3], &[-1.0, 0.5, 2.0]);
+        assert!(items[3].is_nan());
+    }
+
+    #[test]
+    fn test_quicksort_partial_with_nans_first() {
+        let mut items = with_nans();
+        assert_eq!(quicksort_partial(&mut items, NanPolicy::First), Ok(()));
+        assert!(items[..3].iter().all(|item| item.is_nan()));
+        assert_eq!(&items[3..], &SORTED);
+    }
+
+    #[test]
+    fn test_quicksort_partial_with_nans_last() {
+        let mut items = with_nans();
+        assert_eq!(quicksort_partial(&mut items, NanPolicy::Last), Ok(()));
+        assert_eq!(&items[..7], &SORTED);
+        assert!(items[7..].iter().all(|item| item.is_nan()));
+    }
+
+    #[test]
+    fn test_quicksort_partial_rejects_nans() {
+        let mut items = with_nans();
+        assert_eq!(
+            quicksort_partial(&mut items, NanPolicy::Error),
+            Err(PartialOrderError::Incomparable)
+        );
+        assert_eq!(format!("{:?}", items), format!("{:?}", with_nans()));
+
+        let mut items = SORTED.to_vec();
+        items.reverse();
+        assert_eq!(quicksort_partial(&mut items, NanPolicy::Error), Ok(()));
+        assert_eq!(items, SORTED);
+    }
+
+    #[derive(PartialEq, Debug)]
+    struct Divisor(u32);
+
+    /// ordered by divisibility, so 2 and 3 can not be compared
+    impl PartialOrd for Divisor {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            if self.0 == other.0 {
+                Some(Ordering::Equal)
+            } else if other.0.is_multiple_of(self.0) {
+                Some(Ordering::Less)
+            } else if self.0.is_multiple_of(other.0) {
+                Some(Ordering::Greater)
+            } else {
+                None
+            }
+        }
+    }
+
+    #[test]
+    fn test_incomparable_pairs_are_reported() {
+        let mut items = vec![Divisor(4), Divisor(3), Divisor(2)];
+        assert_eq!(
+            quicksort_partial(&mut items, NanPolicy::Error),
+            Err(PartialOrderError::Incomparable)
+        );
+        let mut items = vec![Divisor(4), Divisor(3), Divisor(2)];
+        assert_eq!(
+            bubblesort_partial(&mut items, NanPolicy::Error),
+            Err(PartialOrderError::Incomparable)
+        );
+        let mut items = vec![Divisor(8), Divisor(2), Divisor(4)];
+        assert_eq!(bubblesort_partial(&mut items, NanPolicy::Error), Ok(()));
+        assert_eq!(items, [Divisor(2), Divisor(4), Divisor(8)]);
+    }
+
+    #[test]
+    fn test_bubblesort_partial_keeps_nans_in_order() {
+        let mut items = vec![
+            (f64::NAN, 0),
+            (1.0, 1),
+            (f64::NAN, 2),
+            (1.0, 3),
+            (0.0, 4),
+        ];
+        assert_eq!(bubblesort_partial(&mut items, NanPolicy::Last), Ok(()));
+        let order: Vec<usize> = items.iter().map(|item| item.1).collect();
+        assert_eq!(order, [4, 1, 3, 0, 2]);
+
+        assert_eq!(bubblesort_partial(&mut items, NanPolicy::First), Ok(()));
+        let order: Vec<usize> = items.iter().map(|item| item.1).collect();
+        assert_eq!(order, [0, 2, 4, 1, 3]);
+    }
+
+    #[test]
+    fn test_partial_sorts_are_panic_safe() {
+        test_panic_safety(|items| quicksort_partial(items, NanPolicy::Last).unwrap(), 30);
+        test_panic_safety(|items| bubblesort_partial(items, NanPolicy::First).unwrap(), 20);
+    }
+
+    #[test]
+    fn test_float_sorts_conformance() {
+        crate::sorting::test_helpers::test_sort_conformance(quicksort_floats::<f64>);
+        crate::sorting::test_helpers::test_sort_conformance(bubblesort_floats::<f32>);
+    }
+}
@@ -0,0 +1,390 @@
+use std::cmp::Ordering;
+
+use super::block_partition::block_partition;
+use super::heapsort::heapsort_impl;
+use super::insertionsort::insertionsort_impl;
+use super::instrumented::NoProbe;
+
+/// Slices at or below this length are sorted with insertion sort
+const MAX_INSERTION: usize = 20;
+
+/// Slices at least this long take the pivot from the medians of three groups of three elements
+const SHORTEST_MEDIAN_OF_MEDIANS: usize = 50;
+
+/// Slices shorter than this are not fixed up with a partial insertion sort
+const SHORTEST_SHIFTING: usize = 50;
+
+/// The most adjacent pairs that the partial insertion sort puts in order before giving up
+const MAX_STEPS: usize = 5;
+
+/// Sorts the elements with pattern-defeating quicksort by Orson Peters. Like `block_quicksort` it
+/// partitions without branch mispredictions, and it adapts to the patterns found in real data:
+/// slices that look sorted or reversed are finished in linear time, runs of elements equal to an
+/// earlier pivot are gathered in one pass, unbalanced partitions break up patterns by swapping a few
+/// elements, and if that keeps failing it falls back to heapsort. Takes O(n log n) time in the worst
+/// case. Not stable.
+pub fn pdqsort<T: PartialOrd>(elements: &mut [T]) {
+    pdqsort_impl(elements, &mut |a: &T, b: &T| a < b);
+}
+
+/// Like `pdqsort`, but with a comparator function.
+/// If `compare` panics, the elements are left in some permutation of the input.
+pub fn pdqsort_by<T, F: FnMut(&T, &T) -> Ordering>(elements: &mut [T], mut compare: F) {
+    pdqsort_impl(elements, &mut |a: &T, b: &T| {
+        compare(a, b) == Ordering::Less
+    });
+}
+
+/// Like `pdqsort`, but ordered by the key extracted with `key`
+pub fn pdqsort_by_key<T, K: PartialOrd, F: FnMut(&T) -> K>(elements: &mut [T], mut key: F) {
+    pdqsort_impl(elements, &mut |a: &T, b: &T| key(a) < key(b));
+}
+
+/// The unstable sort this crate recommends, currently `pdqsort`
+pub fn sort_unstable<T: PartialOrd>(elements: &mut [T]) {
+    pdqsort(elements);
+}
+
+/// The unstable sort this crate recommends with a comparator function, currently `pdqsort_by`
+pub fn sort_unstable_by<T, F: FnMut(&T, &T) -> Ordering>(elements: &mut [T], compare: F) {
+    pdqsort_by(elements, compare);
+}
+
+/// The unstable sort this crate recommends with a key function, currently `pdqsort_by_key`
+pub fn sort_unstable_by_key<T, K: PartialOrd, F: FnMut(&T) -> K>(elements: &mut [T], key: F) {
+    pdqsort_by_key(elements, key);
+}
+
+pub(crate) fn pdqsort_impl<T, F: FnMut(&T, &T) -> bool>(elements: &mut [T], is_less: &mut F) {
+    // the number of unbalanced partitions that are allowed before switching to heapsort
+    let limit = usize::BITS - elements.len().leading_zeros();
+    recurse(elements, is_less, None, limit);
+}
+
+/// Sorts `elements`, knowing that `pred` is not greater than any of them if it is set
+fn recurse<'a, T, F: FnMut(&T, &T) -> bool>(
+    mut elements: &'a mut [T],
+    is_less: &mut F,
+    mut pred: Option<&'a T>,
+    mut limit: u32,
+) {
+    let mut was_balanced = true;
+    let mut was_partitioned = true;
+
+    loop {
+        let len = elements.len();
+        if len <= MAX_INSERTION {
+            insertionsort_impl(elements, is_less, &mut NoProbe);
+            return;
+        }
+        if limit == 0 {
+            heapsort_impl(elements, is_less, &mut NoProbe);
+            return;
+        }
+
+        // the last partition was unbalanced, the input might have a pattern that fools the pivot choice
+        if !was_balanced {
+            break_patterns(elements);
+            limit -= 1;
+        }
+
+        let (pivot, likely_sorted) = choose_pivot(elements, is_less);
+
+        // the last partition did not move anything and the pivot looks like the input is sorted, so try
+        // to finish the slice with few shifts
+        if was_balanced
+            && was_partitioned
+            && likely_sorted
+            && partial_insertion_sort(elements, is_less)
+        {
+            return;
+        }
+
+        // the pivot equals the predecessor, which is the smallest possible value, so gather all copies of
+        // it at the front. They are sorted already.
+        if let Some(pred) = pred {
+            if !is_less(pred, &elements[pivot]) {
+                let mid = partition_equal(elements, pivot, is_less);
+                elements = &mut elements[mid..];
+                continue;
+            }
+        }
+
+        let (mid, already_partitioned) = partition(elements, pivot, is_less);
+        was_balanced = usize::min(mid, len - mid) >= len / 8;
+        was_partitioned = already_partitioned;
+
+        let (left, right) = elements.split_at_mut(mid);
+        let (pivot, right) = right.split_at_mut(1);
+        let pivot = &pivot[0];
+
+        // only recurse into the shorter side so the stack depth stays logarithmic
+        if left.len() < right.len() {
+            recurse(left, is_less, pred, limit);
+            elements = right;
+            pred = Some(pivot);
+        } else {
+            recurse(right, is_less, Some(pivot), limit);
+            elements = left;
+        }
+    }
+}
+
+/// Returns the index of the pivot and whether the slice looks sorted. A slice that looks reversed is
+/// reversed, which makes it look sorted.
+fn choose_pivot<T, F: FnMut(&T, &T) -> bool>(elements: &mut [T], is_less: &mut F) -> (usize, bool) {
+    // the most swaps sorting the samples takes, if all of them are needed the slice looks reversed
+    const MAX_SWAPS: usize = 4 * 3;
+
+    let len = elements.len();
+    let mut a = len / 4;
+    let mut b = len / 4 * 2;
+    let mut c = len / 4 * 3;
+    let mut swaps = 0;
+
+    // orders two sample indexes by the elements they point to
+    let mut sort2 = |a: &mut usize, b: &mut usize| {
+        if is_less(&elements[*b], &elements[*a]) {
+            std::mem::swap(a, b);
+            swaps += 1;
+        }
+    };
+    let mut sort3 = |a: &mut usize, b: &mut usize, c: &mut usize| {
+        sort2(a, b);
+        sort2(b, c);
+        sort2(a, b);
+    };
+
+    if len >= SHORTEST_MEDIAN_OF_MEDIANS {
+        // Tukey's ninther: every sample is replaced by the median of it and its neighbours
+        for sample in [&mut a, &mut b, &mut c] {
+            let (mut before, mut after) = (*sample - 1, *sample + 1);
+            sort3(&mut before, sample, &mut after);
+        }
+    }
+    sort3(&mut a, &mut b, &mut c);
+
+    if swaps < MAX_SWAPS {
+        (b, swaps == 0)
+    } else {
+        elements.reverse();
+        (len - 1 - b, true)
+    }
+}
+
+/// Partitions the elements around the pivot at `pivot` and returns its new index. The elements before
+/// it are less, the elements after it are not less. Also returns whether no element had to be moved.
+fn partition<T, F: FnMut(&T, &T) -> bool>(
+    elements: &mut [T],
+    pivot: usize,
+    is_less: &mut F,
+) -> (usize, bool) {
+    elements.swap(0, pivot);
+    let (pivot, rest) = elements.split_at_mut(1);
+    let pivot = &pivot[0];
+
+    // skip the elements that are in place already
+    let mut l = 0;
+    let mut r = rest.len();
+    while l < r && is_less(&rest[l], pivot) {
+        l += 1;
+    }
+    while l < r && !is_less(&rest[r - 1], pivot) {
+        r -= 1;
+    }
+    let already_partitioned = l >= r;
+    let mid = l + block_partition(&mut rest[l..r], pivot, &mut |element, pivot| {
+        is_less(element, pivot)
+    });
+
+    // the last element less than the pivot takes the place of the pivot
+    elements.swap(0, mid);
+    (mid, already_partitioned)
+}
+
+/// Moves the elements equal to the pivot at `pivot` to the front and returns how many there are.
+/// No element may be less than the pivot.
+fn partition_equal<T, F: FnMut(&T, &T) -> bool>(
+    elements: &mut [T],
+    pivot: usize,
+    is_less: &mut F,
+) -> usize {
+    elements.swap(0, pivot);
+    let (pivot, rest) = elements.split_at_mut(1);
+    1 + block_partition(rest, &pivot[0], &mut |element, pivot| {
+        !is_less(pivot, element)
+    })
+}
+
+/// Sorts slices that are sorted except for a few elements by moving those elements into place.
+/// Returns whether the slice is sorted, it gives up after fixing `MAX_STEPS` pairs.
+fn partial_insertion_sort<T, F: FnMut(&T, &T) -> bool>(
+    elements: &mut [T],
+    is_less: &mut F,
+) -> bool {
+    let len = elements.len();
+    let mut i = 1;
+    for _ in 0..MAX_STEPS {
+        while i < len && !is_less(&elements[i], &elements[i - 1]) {
+            i += 1;
+        }
+        if i == len {
+            return true;
+        }
+        // shifting elements does not pay off for short slices
+        if len < SHORTEST_SHIFTING {
+            return false;
+        }
+
+        elements.swap(i - 1, i);
+        // the smaller element moves to the left and the greater one to the right until they fit
+        let mut j = i - 1;
+        while j > 0 && is_less(&elements[j], &elements[j - 1]) {
+            elements.swap(j, j - 1);
+            j -= 1;
+        }
+        let mut j = i;
+        while j + 1 < len && is_less(&elements[j + 1], &elements[j]) {
+            elements.swap(j, j + 1);
+            j += 1;
+        }
+    }
+    false
+}
+
+/// Swaps three elements around the middle with pseudo random other ones, to break up the patterns that
+/// made the last partition unbalanced
+fn break_patterns<T>(elements: &mut [T]) {
+    let len = elements.len();
+    // a xorshift generator seeded with the length, so the sort stays deterministic
+    let mut random = len as u32;
+    let mut next = || {
+        random ^= random << 13;
+        random ^= random >> 17;
+        random ^= random << 5;
+        random as usize
+    };
+
+    let mask = len.next_power_of_two() - 1;
+    let middle = len / 4 * 2;
+    for i in 0..3 {
+        let mut other = next() & mask;
+        if other >= len {
+            other -= len;
+        }
+        elements.swap(middle - 1 + i, other);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use crate::sorting::test_helpers::{
+        self, antiqsort, is_sorted, test_panic_safety, test_unstable_sort, InputShape,
+    };
+
+    use super::*;
+
+    test_helpers::basic_sorting_tests!(pdqsort);
+
+    mod sort_unstable_basics {
+        use super::*;
+        test_helpers::basic_sorting_tests!(sort_unstable);
+    }
+
+    #[test]
+    fn test_pdqsort_with_random_items() {
+        test_unstable_sort(&pdqsort, 10, -1000, 1000);
+        test_unstable_sort(&pdqsort, 10_000, -1000, 1000);
+        test_unstable_sort(&pdqsort, 10_000, 0, 3);
+        test_unstable_sort(&sort_unstable, 10_000, i32::MIN, i32::MAX);
+    }
+
+    #[test]
+    fn test_pdqsort_conformance() {
+        test_helpers::test_sort_conformance(pdqsort::<u64>);
+        test_helpers::test_sort_conformance(pdqsort::<String>);
+        test_helpers::test_sort_conformance(|items: &mut [f64]| {
+            pdqsort_by(items, |a, b| a.partial_cmp(b).unwrap())
+        });
+    }
+
+    /// Counts the comparisons pdqsort needs for sorting the items
+    fn comparisons(mut items: Vec<usize>) -> usize {
+        let mut comparisons = 0;
+        pdqsort_by(&mut items, |a, b| {
+            comparisons += 1;
+            a.cmp(b)
+        });
+        assert!(is_sorted(items.iter()));
+        comparisons
+    }
+
+    #[test]
+    fn test_pdqsort_is_linear_on_patterns() {
+        let len = 10_000;
+        // sorted and reversed slices are recognized by the pivot choice and finished in one pass
+        for shape in [
+            InputShape::AllEqual,
+            InputShape::Presorted,
+            InputShape::Reversed,
+        ] {
+            assert!(
+                comparisons(shape.generate(len)) < 2 * len,
+                "{:?} took too many comparisons",
+                shape
+            );
+        }
+
+        // A partition pass either splits at least one value off the slice of an element, or its pivot was
+        // the smallest value of the slice and becomes the predecessor. The next pass then gathers the copies
+        // of that value or splits a value off. So every element takes part in at most two passes per
+        // distinct value, the pivot samples add a few comparisons per pass. The input is seeded, so the
+        // count is the same on every run.
+        let distinct = 4;
+        let mut generator = StdRng::seed_from_u64(0x5eed);
+        let items = (0..len).map(|_| generator.gen_range(0..distinct)).collect();
+        assert!(comparisons(items) < 2 * distinct * len);
+    }
+
+    #[test]
+    fn test_pdqsort_on_pattern_heavy_inputs() {
+        let len: usize = 10_000;
+        let n_log_n = len * len.ilog2() as usize;
+        for shape in InputShape::ALL {
+            assert!(
+                comparisons(shape.generate(len)) < 3 * n_log_n,
+                "{:?} took too many comparisons",
+                shape
+            );
+        }
+    }
+
+    #[test]
+    fn test_pdqsort_resists_adversaries() {
+        let len = 2000;
+        let (mut input, comparisons) = antiqsort(len, |items, compare| pdqsort_by(items, compare));
+        assert!(comparisons < 6 * len * len.ilog2() as usize);
+
+        pdqsort(&mut input);
+        assert_eq!(input, (0..len).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_pdqsort_by_key() {
+        let mut items: Vec<(u32, usize)> =
+            (0..1000).map(|i| ((i * 7919) % 13, i as usize)).collect();
+        sort_unstable_by_key(&mut items, |item| item.0);
+        assert!(is_sorted(items.iter().map(|item| item.0)));
+        sort_unstable_by(&mut items, |a, b| b.1.cmp(&a.1));
+        assert!(items.iter().map(|item| item.1).eq((0..1000).rev()));
+    }
+
+    #[test]
+    fn test_pdqsort_is_panic_safe() {
+        test_panic_safety(pdqsort, 30);
+        test_panic_safety(pdqsort, 1000);
+    }
+}
@@ -0,0 +1,189 @@
+use std::cmp::Ordering;
+
+use super::instrumented::NoProbe;
+use super::mergesort::mergesort_impl;
+use super::quicksort::introsort_impl;
+
+/// Returns the permutation that sorts the elements without moving them: `elements[order[0]]` is the
+/// smallest element, `elements[order[1]]` the next one and so on. Equal elements may appear in any order.
+pub fn argsort<T: PartialOrd>(elements: &[T]) -> Vec<usize> {
+    argsort_by(elements, |a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+}
+
+/// Like `argsort`, but with a comparator function
+pub fn argsort_by<T, F: FnMut(&T, &T) -> Ordering>(elements: &[T], mut compare: F) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..elements.len()).collect();
+    introsort_impl(&mut order, &mut |a: &usize, b: &usize| {
+        compare(&elements[*a], &elements[*b]) == Ordering::Less
+    });
+    order
+}
+
+/// Like `argsort`, but equal elements keep their order, so their indexes are ascending
+pub fn argsort_stable<T: PartialOrd>(elements: &[T]) -> Vec<usize> {
+    argsort_stable_by(elements, |a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+}
+
+/// Like `argsort_stable`, but with a comparator function
+pub fn argsort_stable_by<T, F: FnMut(&T, &T) -> Ordering>(
+    elements: &[T],
+    mut compare: F,
+) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..elements.len()).collect();
+    mergesort_impl(
+        &mut order,
+        &mut |a: &usize, b: &usize| compare(&elements[*a], &elements[*b]) == Ordering::Less,
+        &mut NoProbe,
+    );
+    order
+}
+
+/// Returns true if `permutation` contains every index from 0 to its length exactly once
+pub fn is_permutation(permutation: &[usize]) -> bool {
+    let mut seen = vec![false; permutation.len()];
+    for index in permutation {
+        match seen.get_mut(*index) {
+            Some(seen @ false) => *seen = true,
+            _ => return false,
+        }
+    }
+    true
+}
+
+/// Returns the permutation that undoes `permutation`, so that `inverse[permutation[i]] == i`.
+/// Panics if `permutation` is not a permutation.
+pub fn invert_permutation(permutation: &[usize]) -> Vec<usize> {
+    assert!(is_permutation(permutation), "not a permutation");
+    let mut inverse = vec![0; permutation.len()];
+    for (i, index) in permutation.iter().enumerate() {
+        inverse[*index] = i;
+    }
+    inverse
+}
+
+/// Reorders the elements so that the element at position `i` is the one that was at `permutation[i]`,
+/// which sorts the elements for a permutation returned by `argsort`. Every cycle of the permutation is
+/// rotated with swaps, so no element is cloned. Panics if `permutation` is not a permutation of the same
+/// length as `elements`.
+pub fn apply_permutation_in_place<T>(elements: &mut [T], permutation: &[usize]) {
+    assert_eq!(elements.len(), permutation.len());
+    assert!(is_permutation(permutation), "not a permutation");
+
+    let mut done = vec![false; elements.len()];
+    for start in 0..elements.len() {
+        if done[start] {
+            continue;
+        }
+        // the element for each position of the cycle is swapped in from the next position, until the
+        // element that was at start is back at the last position
+        let mut current = start;
+        loop {
+            done[current] = true;
+            let next = permutation[current];
+            if next == start {
+                break;
+            }
+            elements.swap(current, next);
+            current = next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::seq::SliceRandom;
+
+    use crate::sorting::test_helpers::{is_sorted, random_comparable_list};
+
+    use super::*;
+
+    fn random_permutation(len: usize) -> Vec<usize> {
+        let mut permutation: Vec<usize> = (0..len).collect();
+        permutation.shuffle(&mut rand::thread_rng());
+        permutation
+    }
+
+    #[test]
+    fn test_argsort() {
+        let items = [30, 10, 20, 10];
+        let order = argsort(&items);
+        assert!(is_sorted(order.iter().map(|i| items[*i])));
+        assert!(is_permutation(&order));
+        assert_eq!(argsort::<i32>(&[]), Vec::<usize>::new());
+
+        let items: Vec<i32> = (0..1000).map(|i| (i * 7919) % 101).collect();
+        let order = argsort(&items);
+        assert!(is_sorted(order.iter().map(|i| items[*i])));
+        assert!(is_permutation(&order));
+    }
+
+    #[test]
+    fn test_argsort_stable() {
+        let items = random_comparable_list(1000, 0, 20);
+        let order = argsort_stable(&items);
+        assert!(is_sorted(order.iter().map(|i| &items[*i])));
+        // indexes of equal elements are ascending
+        assert!(order
+            .windows(2)
+            .all(|pair| items[pair[0]] < items[pair[1]] || pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_argsort_by_co_sorts_parallel_arrays() {
+        let names = ["carol", "alice", "bob"];
+        let ages = [35, 30, 25];
+        let order = argsort_by(&names, |a, b| a.cmp(b));
+        let ages: Vec<_> = order.iter().map(|i| ages[*i]).collect();
+        assert_eq!(ages, [30, 25, 35]);
+
+        let order = argsort_stable_by(&names, |a, b| b.len().cmp(&a.len()));
+        assert_eq!(order, [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_is_permutation() {
+        assert!(is_permutation(&[]));
+        assert!(is_permutation(&[2, 0, 1]));
+        assert!(!is_permutation(&[0, 0, 1]));
+        assert!(!is_permutation(&[0, 3, 1]));
+    }
+
+    #[test]
+    fn test_invert_permutation() {
+        assert_eq!(invert_permutation(&[2, 0, 1]), [1, 2, 0]);
+        let permutation = random_permutation(500);
+        let inverse = invert_permutation(&permutation);
+        assert!((0..500).all(|i| inverse[permutation[i]] == i && permutation[inverse[i]] == i));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invert_permutation_rejects_duplicates() {
+        invert_permutation(&[1, 1]);
+    }
+
+    #[test]
+    fn test_apply_permutation_in_place() {
+        let mut items = ['a', 'b', 'c', 'd'];
+        apply_permutation_in_place(&mut items, &[2, 0, 3, 1]);
+        assert_eq!(items, ['c', 'a', 'd', 'b']);
+
+        let original: Vec<String> = (0..300).map(|i| format!("item {}", i)).collect();
+        let permutation = random_permutation(original.len());
+        let mut items = original.clone();
+        apply_permutation_in_place(&mut items, &permutation);
+        assert!((0..items.len()).all(|i| items[i] == original[permutation[i]]));
+
+        // applying the inverse restores the original order
+        apply_permutation_in_place(&mut items, &invert_permutation(&permutation));
+        assert_eq!(items, original);
+    }
+
+    #[test]
+    fn test_apply_argsort_sorts() {
+        let mut items: Vec<i64> = (0..1000).map(|i| (i * 7919) % 1009 - 500).collect();
+        let order = argsort(&items);
+        apply_permutation_in_place(&mut items, &order);
+        assert!(is_sorted(items.iter()));
+    }
+}
@@ -0,0 +1,132 @@
+use crate::prng::PrnGenerator;
+
+/// Decides which element quicksort partitions around
+pub trait PivotStrategy {
+    /// Returns the index of the pivot. `elements` always holds at least two elements.
+    fn select_pivot<T, F: FnMut(&T, &T) -> bool>(&mut self, elements: &[T], is_less: &mut F) -> usize;
+}
+
+impl<P: PivotStrategy + ?Sized> PivotStrategy for &mut P {
+    fn select_pivot<T, F: FnMut(&T, &T) -> bool>(&mut self, elements: &[T], is_less: &mut F) -> usize {
+        (**self).select_pivot(elements, is_less)
+    }
+}
+
+/// Always uses the element in the middle of the slice
+pub struct MiddlePivot;
+
+impl PivotStrategy for MiddlePivot {
+    fn select_pivot<T, F: FnMut(&T, &T) -> bool>(&mut self, elements: &[T], _: &mut F) -> usize {
+        elements.len() / 2
+    }
+}
+
+/// Uses the median of the first, the middle and the last element
+pub struct MedianOfThree;
+
+impl PivotStrategy for MedianOfThree {
+    fn select_pivot<T, F: FnMut(&T, &T) -> bool>(&mut self, elements: &[T], is_less: &mut F) -> usize {
+        let len = elements.len();
+        median_of_three(elements, 0, len / 2, len - 1, is_less)
+    }
+}
+
+/// Tukey's ninther: the median of the medians of three evenly spread groups of three elements.
+/// Short slices use the median of three instead.
+pub struct Ninther;
+
+/// Below this length `Ninther` falls back to `MedianOfThree`
+const NINTHER_THRESHOLD: usize = 64;
+
+impl PivotStrategy for Ninther {
+    fn select_pivot<T, F: FnMut(&T, &T) -> bool>(&mut self, elements: &[T], is_less: &mut F) -> usize {
+        let len = elements.len();
+        if len < NINTHER_THRESHOLD {
+            return MedianOfThree.select_pivot(elements, is_less);
+        }
+        let step = len / 8;
+        let mid = len / 2;
+        let first = median_of_three(elements, 0, step, 2 * step, is_less);
+        let second = median_of_three(elements, mid - step, mid, mid + step, is_less);
+        let third = median_of_three(elements, len - 1 - 2 * step, len - 1 - step, len - 1, is_less);
+        median_of_three(elements, first, second, third, is_less)
+    }
+}
+
+/// Picks a uniformly distributed pivot. Seeding the generator makes the sort reproducible.
+pub struct RandomPivot<G: PrnGenerator> {
+    generator: G,
+}
+
+impl<G: PrnGenerator> RandomPivot<G> {
+    pub fn new(generator: G) -> RandomPivot<G> {
+        RandomPivot { generator }
+    }
+
+    /// Returns the generator, e.g. to continue using it after sorting
+    pub fn into_generator(self) -> G {
+        self.generator
+    }
+}
+
+impl<G: PrnGenerator> PivotStrategy for RandomPivot<G> {
+    fn select_pivot<T, F: FnMut(&T, &T) -> bool>(&mut self, elements: &[T], _: &mut F) -> usize {
+        self.generator.next_below(elements.len() as u64) as usize
+    }
+}
+
+/// Returns the index of the median of the three elements at the given indexes
+pub(crate) fn median_of_three<T, F: FnMut(&T, &T) -> bool>(
+    elements: &[T],
+    mut a: usize,
+    mut b: usize,
+    c: usize,
+    is_less: &mut F,
+) -> usize {
+    if is_less(&elements[b], &elements[a]) {
+        std::mem::swap(&mut a, &mut b);
+    }
+    // now elements[a] <= elements[b]
+    if !is_less(&elements[c], &elements[b]) {
+        b
+    } else if is_less(&elements[c], &elements[a]) {
+        a
+    } else {
+        c
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_less(a: &i32, b: &i32) -> bool {
+        a < b
+    }
+
+    #[test]
+    fn test_median_of_three_for_all_orders() {
+        let orders = [[1, 2, 3], [1, 3, 2], [2, 1, 3], [2, 3, 1], [3, 1, 2], [3, 2, 1]];
+        for order in orders {
+            let median = median_of_three(&order, 0, 1, 2, &mut is_less);
+            assert_eq!(order[median], 2);
+        }
+    }
+
+    #[test]
+    fn test_ninther_picks_the_median_of_sorted_data() {
+        let items: Vec<i32> = (0..900).collect();
+        let pivot = Ninther.select_pivot(&items, &mut is_less);
+        assert_eq!(pivot, 450);
+    }
+
+    #[test]
+    fn test_random_pivot_stays_in_bounds() {
+        let generator = crate::prng::ChaChaGenerator::<20>::from_key([7; 32], [0; 12]);
+        let mut strategy = RandomPivot::new(generator);
+        for len in 2..200 {
+            let items: Vec<i32> = (0..len).collect();
+            assert!(strategy.select_pivot(&items, &mut is_less) < items.len());
+        }
+    }
+}
@@ -1,74 +1,236 @@
-pub fn quicksort<T: PartialOrd + Clone>(elements: &mut [T]) {
-    if elements.len() < 2 {
-        return;
+use std::cmp::Ordering;
+
+use super::heapsort::heapsort_impl;
+use super::insertionsort::insertionsort_impl;
+use super::instrumented::{NoProbe, SortProbe};
+use super::pivot::{MedianOfThree, PivotStrategy};
+
+/// Partitions at or below this length are handed to insertion sort by introsort
+const INSERTION_SORT_THRESHOLD: usize = 16;
+
+/// Sorts the elements using the median of three as pivot
+pub fn quicksort<T: PartialOrd>(elements: &mut [T]) {
+    quicksort_with(elements, MedianOfThree);
+}
+
+/// Sorts the elements, partitioning around the pivots chosen by `strategy`
+pub fn quicksort_with<T: PartialOrd, P: PivotStrategy>(elements: &mut [T], mut strategy: P) {
+    quicksort_impl(elements, &mut |a: &T, b: &T| a < b, &mut strategy, &mut NoProbe);
+}
+
+/// Sorts the elements with a comparator function, equal elements may be reordered.
+/// If `compare` panics, the elements are left in some permutation of the input.
+pub fn quicksort_by<T, F: FnMut(&T, &T) -> Ordering>(elements: &mut [T], compare: F) {
+    quicksort_by_with(elements, MedianOfThree, compare);
+}
+
+/// Sorts the elements with a comparator function and the pivots chosen by `strategy`
+pub fn quicksort_by_with<T, P: PivotStrategy, F: FnMut(&T, &T) -> Ordering>(
+    elements: &mut [T],
+    mut strategy: P,
+    mut compare: F,
+) {
+    quicksort_impl(
+        elements,
+        &mut |a: &T, b: &T| compare(a, b) == Ordering::Less,
+        &mut strategy,
+        &mut NoProbe,
+    );
+}
+
+/// Sorts the elements by the key extracted with `key`. The key is recomputed on every comparison.
+pub fn quicksort_by_key<T, K: PartialOrd, F: FnMut(&T) -> K>(elements: &mut [T], mut key: F) {
+    quicksort_impl(
+        elements,
+        &mut |a: &T, b: &T| key(a) < key(b),
+        &mut MedianOfThree,
+        &mut NoProbe,
+    );
+}
+
+/// Sorts the elements by the key extracted with `key`, calling `key` only once per element.
+pub fn quicksort_by_cached_key<T, K: PartialOrd, F: FnMut(&T) -> K>(
+    elements: &mut [T],
+    key: F,
+) {
+    super::cached_key::sort_by_cached_key(elements, key, |keyed| {
+        quicksort_impl(
+            keyed,
+            &mut |a: &(K, usize), b: &(K, usize)| a.0 < b.0,
+            &mut MedianOfThree,
+            &mut NoProbe,
+        )
+    });
+}
+
+/// Introspective quicksort: behaves like `quicksort` but switches to heapsort once the recursion gets
+/// deeper than 2·log2(n), which guarantees O(n log n) comparisons even on adversarial inputs.
+/// Small partitions are finished with insertion sort.
+pub fn introsort<T: PartialOrd>(elements: &mut [T]) {
+    introsort_impl(elements, &mut |a: &T, b: &T| a < b);
+}
+
+/// Like `introsort`, but with a comparator function
+pub fn introsort_by<T, F: FnMut(&T, &T) -> Ordering>(elements: &mut [T], mut compare: F) {
+    introsort_impl(elements, &mut |a: &T, b: &T| compare(a, b) == Ordering::Less);
+}
+
+/// Like `introsort`, but ordered by the key extracted with `key`
+pub fn introsort_by_key<T, K: PartialOrd, F: FnMut(&T) -> K>(elements: &mut [T], mut key: F) {
+    introsort_impl(elements, &mut |a: &T, b: &T| key(a) < key(b));
+}
+
+pub(crate) fn quicksort_impl<T, F: FnMut(&T, &T) -> bool, P: PivotStrategy, R: SortProbe>(
+    mut elements: &mut [T],
+    is_less: &mut F,
+    strategy: &mut P,
+    probe: &mut R,
+) {
+    probe.entered();
+    // only recurse into the smaller side so the stack depth stays logarithmic
+    while elements.len() >= 2 {
+        let (smaller, larger) = partition_and_split(elements, is_less, strategy, probe);
+        quicksort_impl(smaller, is_less, strategy, probe);
+        elements = larger;
+    }
+    probe.returned();
+}
+
+pub(crate) fn introsort_impl<T, F: FnMut(&T, &T) -> bool>(elements: &mut [T], is_less: &mut F) {
+    let depth_limit = 2 * (usize::BITS - elements.len().leading_zeros());
+    introsort_loop(elements, is_less, depth_limit);
+}
+
+fn introsort_loop<T, F: FnMut(&T, &T) -> bool>(
+    mut elements: &mut [T],
+    is_less: &mut F,
+    mut depth_limit: u32,
+) {
+    loop {
+        if elements.len() <= INSERTION_SORT_THRESHOLD {
+            insertionsort_impl(elements, is_less, &mut NoProbe);
+            return;
+        }
+        if depth_limit == 0 {
+            heapsort_impl(elements, is_less, &mut NoProbe);
+            return;
+        }
+        depth_limit -= 1;
+
+        let (smaller, larger) = partition_and_split(elements, is_less, &mut MedianOfThree, &mut NoProbe);
+        introsort_loop(smaller, is_less, depth_limit);
+        elements = larger;
+    }
+}
+
+/// Partitions the elements and returns the parts left and right of the pivots, smaller one first
+pub(crate) fn partition_and_split<'a, T, F, P, R>(
+    elements: &'a mut [T],
+    is_less: &mut F,
+    strategy: &mut P,
+    probe: &mut R,
+) -> (&'a mut [T], &'a mut [T])
+where
+    F: FnMut(&T, &T) -> bool,
+    P: PivotStrategy,
+    R: SortProbe,
+{
+    probe.partitioning(elements);
+    let pivot_index = strategy.select_pivot(elements, is_less);
+    probe.pivot_chosen(elements, pivot_index);
+    let (left_p, right_p) = partition(elements, pivot_index, is_less, probe);
+    probe.partitioned(elements, left_p, right_p);
+    let (left, rest) = elements.split_at_mut(left_p);
+    let right = &mut rest[right_p - left_p..];
+    if left.len() <= right.len() {
+        (left, right)
+    } else {
+        (right, left)
     }
-    let (left_p, right_p) = partition(elements);
-    quicksort(&mut elements[0..left_p]);
-    quicksort(&mut elements[right_p..]);
 }
 
-/// Partitions elements into left_side, pivots, right_side
+/// Partitions elements into left_side, pivots, right_side around the element at pivot_index.
+/// The pivot is kept at the front while partitioning, so it is only ever compared by reference.
 /// returns the index of the leftmost item still being a pivot and the index of the item right of the last pivot
-fn partition<T: PartialOrd + Clone>(elements: &mut [T]) -> (usize, usize) {
-    let pivot = elements[elements.len() / 2].clone();
-    let mut left_index = usize::MAX;
-    let mut right_index = elements.len();
+pub(crate) fn partition<T, F: FnMut(&T, &T) -> bool, R: SortProbe>(
+    elements: &mut [T],
+    pivot_index: usize,
+    is_less: &mut F,
+    probe: &mut R,
+) -> (usize, usize) {
+    if pivot_index != 0 {
+        elements.swap(0, pivot_index);
+        probe.swapped(elements, 0, pivot_index);
+    }
+    let (pivot, rest) = elements.split_at_mut(1);
+    let pivot = &pivot[0];
+    let mut left_index = 0;
+    let mut right_index = rest.len();
 
     loop {
-        loop {
-            left_index = left_index.wrapping_add(1);
-            if elements[left_index] >= pivot {
-                break;
-            }
+        while left_index < right_index && is_less(&rest[left_index], pivot) {
+            left_index += 1;
         }
 
-        loop {
+        while left_index < right_index && is_less(pivot, &rest[right_index - 1]) {
             right_index -= 1;
-            if elements[right_index] <= pivot {
-                break;
-            }
         }
 
         if left_index >= right_index {
             break;
         }
 
-        unsafe { swap_elements_unsafe(elements, left_index, right_index) }
+        // when the scans stop next to each other, the element between them equals the pivot and stays
+        right_index -= 1;
+        if left_index != right_index {
+            rest.swap(left_index, right_index);
+            probe.swapped(rest, left_index, right_index);
+        }
+        left_index += 1;
+    }
+
+    // every element of rest left of left_index is not greater than the pivot, every other one is not less.
+    // so the pivot belongs right behind the left side
+    let pivot_index = left_index;
+    if pivot_index != 0 {
+        elements.swap(0, pivot_index);
+        probe.swapped(elements, 0, pivot_index);
     }
 
-    // find the two indexes of elements where the pivots begin and end 
+    // find the two indexes of elements where the pivots begin and end
     // when there are multiple copies of the pivot
+    let mut left_index = pivot_index;
+    let mut right_index = pivot_index + 1;
 
-    while left_index > 0  {
-        left_index -=1;
-        if elements[left_index] != pivot {
-            left_index += 1;
-            break;
-        }
+    while left_index > 0 && !is_less(&elements[left_index - 1], &elements[pivot_index]) {
+        left_index -= 1;
     }
 
-    while right_index < elements.len() -1 && elements[right_index] == pivot {
+    while right_index < elements.len() && !is_less(&elements[pivot_index], &elements[right_index]) {
         right_index += 1;
     }
 
     (left_index, right_index)
 }
 
-#[inline]
-unsafe fn swap_elements_unsafe<T: PartialOrd>(elements: &mut [T], index1: usize, index2: usize) {
-    assert!(index1 != index2);
-    let left = &elements[index1] as *const T as *mut T;
-    let right = &elements[index2] as *const T as *mut T;
-    std::mem::swap(&mut *left, &mut *right);
-}
-
 #[cfg(test)]
 mod tests {
-    use crate::sorting::test_helpers::{is_sorted, test_unstable_sort, self};
+    use crate::sorting::test_helpers::{antiqsort, is_sorted, test_panic_safety, test_unstable_sort, self};
 
-    use super::quicksort;
+    use crate::prng::ChaChaGenerator;
+    use crate::sorting::pivot::{MedianOfThree, MiddlePivot, Ninther, RandomPivot};
+
+    use super::{
+        introsort, introsort_by, introsort_by_key, quicksort, quicksort_by,
+        quicksort_by_cached_key, quicksort_by_key, quicksort_by_with, quicksort_with,
+    };
     test_helpers::basic_sorting_tests!(quicksort);
 
+    mod introsort_basics {
+        use super::*;
+        test_helpers::basic_sorting_tests!(introsort);
+    }
+
     #[test]
     fn test_quicksort_with_two_items() {
         let mut items = [-1, 1];
@@ -105,4 +267,171 @@ mod tests {
     fn test_quicksort_with_lots_of_double_random_items() {
         test_unstable_sort(&quicksort, 2000, -1000, 1000);
     }
+
+    #[test]
+    fn test_quicksort_by_descending() {
+        let mut items = [3, 1, 4, 1, 5, 9, 2, 6];
+        quicksort_by(&mut items, |a, b| b.cmp(a));
+        assert_eq!(items, [9, 6, 5, 4, 3, 2, 1, 1]);
+    }
+
+    #[test]
+    fn test_quicksort_by_key() {
+        let mut items = [(3, 'c'), (1, 'a'), (2, 'b'), (0, 'z')];
+        quicksort_by_key(&mut items, |item| item.1);
+        assert_eq!(items, [(1, 'a'), (2, 'b'), (3, 'c'), (0, 'z')]);
+    }
+
+    #[test]
+    fn test_quicksort_by_cached_key() {
+        let mut items: Vec<String> = (0..200).map(|i| ((i * 7919) % 200).to_string()).collect();
+        let mut key_calls = 0;
+        quicksort_by_cached_key(&mut items, |item| {
+            key_calls += 1;
+            item.parse::<i32>().unwrap()
+        });
+        let expected: Vec<String> = (0..200).map(|i| i.to_string()).collect();
+        assert_eq!(items, expected);
+        assert_eq!(key_calls, 200);
+    }
+
+    #[test]
+    fn test_introsort_with_lots_of_double_random_items() {
+        test_unstable_sort(&introsort, 2000, -1000, 1000);
+        test_unstable_sort(&introsort, 2000, -10, 10);
+    }
+
+    #[test]
+    fn test_introsort_by_key() {
+        let mut items: Vec<(i32, usize)> = (0..300).map(|i| ((i * 37) % 101, i as usize)).collect();
+        introsort_by_key(&mut items, |item| item.0);
+        assert!(items.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+    }
+
+    fn n_log_n(len: usize) -> usize {
+        len * (usize::BITS - len.leading_zeros()) as usize
+    }
+
+    #[test]
+    fn test_antiqsort_makes_plain_quicksort_quadratic() {
+        let len = 2000;
+        let (_, comparisons) = antiqsort(len, |items, compare| quicksort_by(items, compare));
+        assert!(comparisons > len * len / 8);
+    }
+
+    #[test]
+    fn test_introsort_survives_antiqsort() {
+        let len = 2000;
+        let (mut input, comparisons) = antiqsort(len, |items, compare| introsort_by(items, compare));
+        assert!(comparisons < 6 * n_log_n(len));
+
+        introsort(&mut input);
+        assert_eq!(input, (0..len).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_introsort_on_quicksort_killer_input() {
+        let len = 2000;
+        let (input, _) = antiqsort(len, |items, compare| quicksort_by(items, compare));
+
+        let mut items = input.clone();
+        let mut comparisons = 0;
+        introsort_by(&mut items, |a, b| {
+            comparisons += 1;
+            a.cmp(b)
+        });
+        assert!(comparisons < 6 * n_log_n(len));
+        assert_eq!(items, (0..len).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_quicksort_with_every_pivot_strategy() {
+        test_unstable_sort(&|items: &mut [i32]| quicksort_with(items, MiddlePivot), 2000, -100, 100);
+        test_unstable_sort(&|items: &mut [i32]| quicksort_with(items, MedianOfThree), 2000, -100, 100);
+        test_unstable_sort(&|items: &mut [i32]| quicksort_with(items, Ninther), 2000, -100, 100);
+
+        let seeded_random = |items: &mut [i32]| {
+            let generator = ChaChaGenerator::<20>::from_key([1; 32], [2; 12]);
+            quicksort_with(items, RandomPivot::new(generator))
+        };
+        test_unstable_sort(&seeded_random, 2000, -100, 100);
+    }
+
+    #[test]
+    fn test_seeded_random_pivots_are_reproducible() {
+        let count_comparisons = || {
+            let generator = ChaChaGenerator::<20>::from_key([3; 32], [4; 12]);
+            let mut items: Vec<i32> = (0..1000).map(|i| (i * 7919) % 1000).collect();
+            let mut comparisons = 0;
+            quicksort_by_with(&mut items, RandomPivot::new(generator), |a, b| {
+                comparisons += 1;
+                a.cmp(b)
+            });
+            assert_eq!(items, (0..1000).collect::<Vec<_>>());
+            comparisons
+        };
+        assert_eq!(count_comparisons(), count_comparisons());
+    }
+
+    #[test]
+    fn test_median_of_three_handles_sorted_input_in_n_log_n() {
+        let len = 4096;
+        let mut items: Vec<usize> = (0..len).collect();
+        let mut comparisons = 0;
+        quicksort_by_with(&mut items, MedianOfThree, |a, b| {
+            comparisons += 1;
+            a.cmp(b)
+        });
+        assert!(comparisons < 4 * n_log_n(len));
+    }
+
+    #[derive(PartialEq, PartialOrd, Debug)]
+    struct Unclonable(i32);
+
+    #[test]
+    fn test_quicksort_without_clone() {
+        let mut items: Vec<Unclonable> = (0..500).map(|i| Unclonable((i * 7919) % 500)).collect();
+        quicksort(&mut items);
+        assert!(items.iter().zip(0..).all(|(item, i)| item.0 == i));
+
+        introsort(&mut items);
+        assert!(items.iter().zip(0..).all(|(item, i)| item.0 == i));
+    }
+
+    #[test]
+    fn test_quicksort_sorts_trait_objects() {
+        let mut items: Vec<Box<dyn Fn() -> i32>> = (0..50)
+            .map(|i| Box::new(move || (i * 31) % 50) as Box<dyn Fn() -> i32>)
+            .collect();
+        quicksort_by_key(&mut items, |item| item());
+        assert!(items.iter().zip(0..).all(|(item, i)| item() == i));
+    }
+
+    #[test]
+    fn test_quicksort_does_not_run_off_the_slice_with_nans() {
+        let mut items: Vec<f64> = (0..300).map(|i| if i % 7 == 0 { f64::NAN } else { i as f64 }).collect();
+        quicksort(&mut items);
+        assert_eq!(items.len(), 300);
+        assert_eq!(items.iter().filter(|item| item.is_nan()).count(), 43);
+
+        let mut items = vec![f64::NAN; 50];
+        introsort(&mut items);
+        assert!(items.iter().all(|item| item.is_nan()));
+    }
+
+    #[test]
+    fn test_quicksort_is_panic_safe() {
+        test_panic_safety(quicksort, 40);
+        test_panic_safety(introsort, 40);
+        test_panic_safety(|items| quicksort_by(items, |a, b| a.partial_cmp(b).unwrap()), 40);
+        test_panic_safety(|items| quicksort_with(items, Ninther), 100);
+    }
+
+    #[test]
+    fn test_quicksort_conformance() {
+        test_helpers::test_sort_conformance(quicksort::<i64>);
+        test_helpers::test_sort_conformance(quicksort::<String>);
+        test_helpers::test_sort_conformance(introsort::<i64>);
+        test_helpers::test_sort_conformance(|items: &mut [u8]| quicksort_with(items, Ninther));
+    }
 }
@@ -0,0 +1,326 @@
+use super::cached_key::sort_by_cached_key;
+use super::quicksort_by;
+
+/// The number of bits `radix_sort` looks at in every pass
+const DEFAULT_DIGIT_BITS: u32 = 8;
+
+/// Buckets of at most this many strings are finished with quicksort by `msd_radix_sort`
+const MSD_QUICKSORT_CUTOFF: usize = 32;
+
+/// Integers that radix sort can order by their bits
+pub trait RadixKey: Copy {
+    /// The number of bits of the type
+    const BITS: u32;
+
+    /// Maps the value to an unsigned integer with the same order. For signed integers the sign bit is
+    /// flipped, so negative numbers come before positive ones.
+    fn to_radix_key(self) -> u64;
+}
+
+macro_rules! unsigned_radix_key {
+    ($($t: ty),*) => {$(
+        impl RadixKey for $t {
+            const BITS: u32 = <$t>::BITS;
+
+            fn to_radix_key(self) -> u64 {
+                self as u64
+            }
+        }
+    )*};
+}
+
+macro_rules! signed_radix_key {
+    ($($t: ty => $unsigned: ty),*) => {$(
+        impl RadixKey for $t {
+            const BITS: u32 = <$t>::BITS;
+
+            fn to_radix_key(self) -> u64 {
+                (self as $unsigned ^ (1 << (<$t>::BITS - 1))) as u64
+            }
+        }
+    )*};
+}
+
+unsigned_radix_key!(u8, u16, u32, u64, usize);
+signed_radix_key!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, isize => usize);
+
+/// Sorts integers with a least significant digit radix sort, looking at 8 bits per pass.
+/// Takes O(n) time per pass and a scratch buffer of n elements.
+pub fn radix_sort<T: RadixKey>(elements: &mut [T]) {
+    radix_sort_with_digit_bits(elements, DEFAULT_DIGIT_BITS);
+}
+
+/// Like `radix_sort`, but looks at `digit_bits` bits per pass, which has to be between 1 and 16.
+/// Wider digits need fewer passes but a larger table of counters.
+pub fn radix_sort_with_digit_bits<T: RadixKey>(elements: &mut [T], digit_bits: u32) {
+    assert!(
+        (1..=16).contains(&digit_bits),
+        "digit_bits must be between 1 and 16"
+    );
+    lsd_radix_sort(
+        elements,
+        |element| element.to_radix_key(),
+        T::BITS,
+        digit_bits,
+    );
+}
+
+/// Sorts the elements stabelly by an integer key with a least significant digit radix sort.
+/// The key is computed once per element, the elements are moved into place afterwards.
+pub fn radix_sort_by_key<T, K: RadixKey, F: FnMut(&T) -> K>(elements: &mut [T], mut key: F) {
+    sort_by_cached_key(
+        elements,
+        |element| key(element).to_radix_key(),
+        |keyed| lsd_radix_sort(keyed, |entry| entry.0, K::BITS, DEFAULT_DIGIT_BITS),
+    );
+}
+
+/// Sorts byte strings like `&str`, `String` or `Vec<u8>` lexicographically with a most significant digit
+/// radix sort. Elements are distributed into buckets by their byte at the current depth in place, the
+/// buckets are then sorted by the next byte. Small buckets are handed to quicksort.
+/// Equal elements may be reordered.
+pub fn msd_radix_sort<T: AsRef<[u8]>>(elements: &mut [T]) {
+    msd_radix_sort_impl(elements, 0);
+}
+
+fn lsd_radix_sort<T: Copy, F: Fn(&T) -> u64>(
+    elements: &mut [T],
+    key: F,
+    key_bits: u32,
+    digit_bits: u32,
+) {
+    if elements.len() < 2 {
+        return;
+    }
+    let mask = (1u64 << digit_bits) - 1;
+    let mut counts = vec![0; 1 << digit_bits];
+    let mut buffer = elements.to_vec();
+    let mut sorted_in_buffer = false;
+
+    let mut shift = 0;
+    while shift < key_bits {
+        let digit = |element: &T| ((key(element) >> shift) & mask) as usize;
+        let (source, target): (&[T], &mut [T]) = if sorted_in_buffer {
+            (&buffer, elements)
+        } else {
+            (elements, &mut buffer)
+        };
+
+        counts.iter_mut().for_each(|count| *count = 0);
+        for element in source {
+            counts[digit(element)] += 1;
+        }
+        // if every element has the same digit, this pass would not change anything
+        if counts[digit(&source[0])] != source.len() {
+            let mut next = 0;
+            for count in counts.iter_mut() {
+                let bucket_len = *count;
+                *count = next;
+                next += bucket_len;
+            }
+            for element in source {
+                let bucket = &mut counts[digit(element)];
+                target[*bucket] = *element;
+                *bucket += 1;
+            }
+            sorted_in_buffer = !sorted_in_buffer;
+        }
+        shift += digit_bits;
+    }
+
+    if sorted_in_buffer {
+        elements.copy_from_slice(&buffer);
+    }
+}
+
+fn msd_radix_sort_impl<T: AsRef<[u8]>>(elements: &mut [T], mut depth: usize) {
+    loop {
+        if elements.len() <= MSD_QUICKSORT_CUTOFF {
+            // all elements share the first depth bytes
+            quicksort_by(elements, |a, b| {
+                a.as_ref()[depth..].cmp(&b.as_ref()[depth..])
+            });
+            return;
+        }
+
+        // bucket 0 holds the strings that end at this depth, bucket b + 1 the ones with byte b
+        let bucket_of = |element: &T| {
+            element
+                .as_ref()
+                .get(depth)
+                .map_or(0, |byte| *byte as usize + 1)
+        };
+        let mut ends = [0; 257];
+        for element in elements.iter() {
+            ends[bucket_of(element)] += 1;
+        }
+        let mut starts = [0; 257];
+        let mut next = 0;
+        for bucket in 0..257 {
+            starts[bucket] = next;
+            next += ends[bucket];
+            ends[bucket] = next;
+        }
+
+        // american flag sort: swap every element into the next free place of its bucket
+        let mut free = starts;
+        for bucket in 0..257 {
+            while free[bucket] < ends[bucket] {
+                let target = bucket_of(&elements[free[bucket]]);
+                if target == bucket {
+                    free[bucket] += 1;
+                } else {
+                    elements.swap(free[bucket], free[target]);
+                    free[target] += 1;
+                }
+            }
+        }
+
+        // if all strings continue with the same byte, go on with the next byte without recursing,
+        // so long common prefixes do not overflow the stack
+        if (1..257).any(|bucket| ends[bucket] - starts[bucket] == elements.len()) {
+            depth += 1;
+            continue;
+        }
+        for bucket in 1..257 {
+            msd_radix_sort_impl(&mut elements[starts[bucket]..ends[bucket]], depth + 1);
+        }
+        return;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sorting::test_helpers::{self, is_stabelly_sorted, random_comparable_list};
+    use rand::Rng;
+
+    use super::*;
+
+    test_helpers::basic_sorting_tests!(radix_sort);
+
+    fn check_against_std<T: RadixKey + Ord + std::fmt::Debug>(mut items: Vec<T>) {
+        let mut expected = items.clone();
+        expected.sort();
+        radix_sort(&mut items);
+        assert_eq!(items, expected);
+    }
+
+    fn random_vec<T>(len: usize) -> Vec<T>
+    where
+        rand::distributions::Standard: rand::distributions::Distribution<T>,
+    {
+        let mut generator = rand::thread_rng();
+        (0..len).map(|_| generator.gen()).collect()
+    }
+
+    #[test]
+    fn test_radix_sort_unsigned_integers() {
+        check_against_std(random_vec::<u8>(1000));
+        check_against_std(random_vec::<u16>(1000));
+        check_against_std(random_vec::<u32>(1000));
+        check_against_std(random_vec::<u64>(1000));
+        check_against_std(random_vec::<usize>(1000));
+        check_against_std(vec![u64::MAX, 0, 1, u64::MAX - 1, 1 << 63]);
+    }
+
+    #[test]
+    fn test_radix_sort_signed_integers() {
+        check_against_std(random_vec::<i8>(1000));
+        check_against_std(random_vec::<i16>(1000));
+        check_against_std(random_vec::<i32>(1000));
+        check_against_std(random_vec::<i64>(1000));
+        check_against_std(random_vec::<isize>(1000));
+        check_against_std(vec![
+            i64::MAX,
+            i64::MIN,
+            -1,
+            0,
+            1,
+            i64::MIN + 1,
+            i64::MAX - 1,
+        ]);
+        check_against_std(vec![i8::MIN, -1, i8::MAX, 0]);
+    }
+
+    #[test]
+    fn test_radix_sort_with_every_digit_width() {
+        let items = random_vec::<i32>(500);
+        let mut expected = items.clone();
+        expected.sort();
+        for digit_bits in 1..=16 {
+            let mut sorted = items.clone();
+            radix_sort_with_digit_bits(&mut sorted, digit_bits);
+            assert_eq!(sorted, expected, "digit width {}", digit_bits);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_radix_sort_rejects_wide_digits() {
+        radix_sort_with_digit_bits(&mut [1u32, 2], 17);
+    }
+
+    #[test]
+    fn test_radix_sort_by_key_is_stable() {
+        for len in [1, 2, 100, 5000] {
+            let mut data = random_comparable_list(len, -50, 50);
+            radix_sort_by_key(&mut data, |item| item.value());
+            assert!(is_stabelly_sorted(&data));
+        }
+    }
+
+    #[test]
+    fn test_radix_sort_by_key_moves_records() {
+        let mut records: Vec<(String, u32)> = (0..300)
+            .map(|i| (format!("record {}", i), (i * 7919) % 300))
+            .collect();
+        radix_sort_by_key(&mut records, |record| record.1);
+        assert!(records.iter().zip(0..).all(|(record, i)| record.1 == i));
+        assert_eq!(records[0].0, "record 0");
+    }
+
+    #[test]
+    fn test_msd_radix_sort_strings() {
+        let mut generator = rand::thread_rng();
+        let mut words: Vec<String> = (0..3000)
+            .map(|_| {
+                let len = generator.gen_range(0..8);
+                (0..len)
+                    .map(|_| generator.gen_range(b'a'..=b'e') as char)
+                    .collect()
+            })
+            .collect();
+        let mut expected = words.clone();
+        expected.sort();
+        msd_radix_sort(&mut words);
+        assert_eq!(words, expected);
+    }
+
+    #[test]
+    fn test_msd_radix_sort_bytes_and_prefixes() {
+        let mut items: Vec<&[u8]> = vec![b"ab", b"", b"a", b"\xff", b"abc", b"ab\x00", b"b", b"a"];
+        items.extend(std::iter::repeat_n(&b"abd"[..], 40));
+        let mut expected = items.clone();
+        expected.sort();
+        msd_radix_sort(&mut items);
+        assert_eq!(items, expected);
+    }
+
+    #[test]
+    fn test_msd_radix_sort_long_common_prefixes() {
+        let prefix = "x".repeat(100_000);
+        let mut items: Vec<String> = (0..100).rev().map(|i| format!("{}{}", prefix, i)).collect();
+        let mut expected = items.clone();
+        expected.sort();
+        msd_radix_sort(&mut items);
+        assert_eq!(items, expected);
+    }
+
+    #[test]
+    fn test_radix_sort_conformance() {
+        test_helpers::test_sort_conformance(radix_sort::<u32>);
+        test_helpers::test_sort_conformance(radix_sort::<i8>);
+        test_helpers::test_sort_conformance(msd_radix_sort::<String>);
+        test_helpers::test_stable_sort_conformance(|items| radix_sort_by_key(items, |item| item.value()));
+    }
+}
@@ -0,0 +1,304 @@
+use std::cmp::Ordering;
+
+use super::heapsort::heapsort_impl;
+use super::insertionsort::insertionsort_impl;
+use super::instrumented::NoProbe;
+use super::pivot::{MedianOfThree, PivotStrategy};
+use super::quicksort::{introsort_impl, partition};
+use crate::collections::{sift_down, sift_up};
+
+/// Slices at or below this length are finished with insertion sort while selecting
+const INSERTION_SORT_THRESHOLD: usize = 16;
+
+/// How `select_nth_with` chooses the pivots it partitions around
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub enum SelectionMode {
+    /// Median of three pivots. Takes linear time on average, but quadratic time on adversarial inputs.
+    #[default]
+    Quickselect,
+    /// The median of the medians of groups of five elements. Slower on average, but guarantees linear time
+    /// in the worst case.
+    MedianOfMedians,
+}
+
+/// Reorders the elements so that the element at `k` is the one that would be there if the slice was sorted.
+/// Every element before it is not greater and every element after it is not less than it.
+/// Returns the element at `k`. Panics if `k` is out of bounds.
+pub fn select_nth<T: PartialOrd>(elements: &mut [T], k: usize) -> &mut T {
+    select_nth_with(elements, k, SelectionMode::Quickselect)
+}
+
+/// Like `select_nth`, but chooses the pivots as described by `mode`
+pub fn select_nth_with<T: PartialOrd>(elements: &mut [T], k: usize, mode: SelectionMode) -> &mut T {
+    select_impl(elements, k, &mut |a: &T, b: &T| a < b, mode);
+    &mut elements[k]
+}
+
+/// Like `select_nth`, but with a comparator function
+pub fn select_nth_by<T, F: FnMut(&T, &T) -> Ordering>(
+    elements: &mut [T],
+    k: usize,
+    mut compare: F,
+) -> &mut T {
+    select_impl(
+        elements,
+        k,
+        &mut |a: &T, b: &T| compare(a, b) == Ordering::Less,
+        SelectionMode::Quickselect,
+    );
+    &mut elements[k]
+}
+
+/// Returns the median of the elements, reordering them like `select_nth`.
+/// For an even number of elements the lower one of the two middle elements is returned.
+pub fn median<T: PartialOrd>(elements: &mut [T]) -> Option<&mut T> {
+    if elements.is_empty() {
+        return None;
+    }
+    let middle = (elements.len() - 1) / 2;
+    Some(select_nth(elements, middle))
+}
+
+/// Moves the `k` smallest elements to the front of the slice in sorted order, the other elements are left
+/// in an unspecified order. Takes O(n + k log k) time on average.
+pub fn partial_sort<T: PartialOrd>(elements: &mut [T], k: usize) {
+    partial_sort_impl(elements, k, &mut |a: &T, b: &T| a < b);
+}
+
+/// Like `partial_sort`, but with a comparator function
+pub fn partial_sort_by<T, F: FnMut(&T, &T) -> Ordering>(
+    elements: &mut [T],
+    k: usize,
+    mut compare: F,
+) {
+    partial_sort_impl(elements, k, &mut |a: &T, b: &T| {
+        compare(a, b) == Ordering::Less
+    });
+}
+
+/// Returns the `k` greatest items, greatest first. Only `k` items are kept in a heap at any time, so this
+/// takes O(n log k) time and O(k) memory.
+pub fn top_k<T: PartialOrd, I: IntoIterator<Item = T>>(items: I, k: usize) -> Vec<T> {
+    top_k_impl(items, k, &mut |a: &T, b: &T| a < b)
+}
+
+/// Like `top_k`, but with a comparator function
+pub fn top_k_by<T, I: IntoIterator<Item = T>, F: FnMut(&T, &T) -> Ordering>(
+    items: I,
+    k: usize,
+    mut compare: F,
+) -> Vec<T> {
+    top_k_impl(items, k, &mut |a: &T, b: &T| {
+        compare(a, b) == Ordering::Less
+    })
+}
+
+fn select_impl<T, F: FnMut(&T, &T) -> bool>(
+    mut elements: &mut [T],
+    mut k: usize,
+    is_less: &mut F,
+    mode: SelectionMode,
+) {
+    assert!(
+        k < elements.len(),
+        "k is {} but the length is {}",
+        k,
+        elements.len()
+    );
+    loop {
+        if elements.len() <= INSERTION_SORT_THRESHOLD {
+            insertionsort_impl(elements, is_less, &mut NoProbe);
+            return;
+        }
+        let pivot_index = match mode {
+            SelectionMode::Quickselect => MedianOfThree.select_pivot(elements, is_less),
+            SelectionMode::MedianOfMedians => median_of_medians(elements, is_less),
+        };
+        let (start, end) = partition(elements, pivot_index, is_less, &mut NoProbe);
+        if k < start {
+            elements = &mut elements[..start];
+        } else if k >= end {
+            elements = &mut elements[end..];
+            k -= end;
+        } else {
+            return;
+        }
+    }
+}
+
+/// Sorts every group of five elements, gathers the medians of the groups at the front and selects
+/// their median. Returns its index.
+fn median_of_medians<T, F: FnMut(&T, &T) -> bool>(elements: &mut [T], is_less: &mut F) -> usize {
+    let groups = elements.len().div_ceil(5);
+    for group in 0..groups {
+        let start = group * 5;
+        let end = usize::min(start + 5, elements.len());
+        insertionsort_impl(&mut elements[start..end], is_less, &mut NoProbe);
+        elements.swap(group, (start + end) / 2);
+    }
+    let middle = groups / 2;
+    select_impl(
+        &mut elements[..groups],
+        middle,
+        is_less,
+        SelectionMode::MedianOfMedians,
+    );
+    middle
+}
+
+fn partial_sort_impl<T, F: FnMut(&T, &T) -> bool>(elements: &mut [T], k: usize, is_less: &mut F) {
+    if k == 0 {
+        return;
+    }
+    if k < elements.len() {
+        select_impl(elements, k - 1, is_less, SelectionMode::Quickselect);
+    }
+    let k = usize::min(k, elements.len());
+    introsort_impl(&mut elements[..k], is_less);
+}
+
+fn top_k_impl<T, I: IntoIterator<Item = T>, F: FnMut(&T, &T) -> bool>(
+    items: I,
+    k: usize,
+    is_less: &mut F,
+) -> Vec<T> {
+    if k == 0 {
+        return Vec::new();
+    }
+    // a min heap of the greatest items seen so far
+    let is_greater = &mut |a: &T, b: &T| is_less(b, a);
+    let swap = &mut |elements: &mut [T], a, b| elements.swap(a, b);
+    let mut heap = Vec::with_capacity(k);
+    for item in items {
+        if heap.len() < k {
+            heap.push(item);
+            let last = heap.len() - 1;
+            sift_up(&mut heap, last, is_greater, swap);
+        } else if is_greater(&item, &heap[0]) {
+            heap[0] = item;
+            sift_down(&mut heap, 0, is_greater, swap);
+        }
+    }
+    heapsort_impl(&mut heap, is_greater, &mut NoProbe);
+    heap
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::seq::SliceRandom;
+
+    use crate::sorting::test_helpers::antiqsort;
+
+    use super::*;
+
+    fn check_selection(items: &[i32], k: usize, mode: SelectionMode) {
+        let mut sorted = items.to_vec();
+        sorted.sort();
+        let mut selected = items.to_vec();
+        assert_eq!(*select_nth_with(&mut selected, k, mode), sorted[k]);
+        assert!(selected[..k].iter().all(|item| *item <= sorted[k]));
+        assert!(selected[k + 1..].iter().all(|item| *item >= sorted[k]));
+    }
+
+    #[test]
+    fn test_select_nth_at_every_position() {
+        let mut items: Vec<i32> = (0..100).map(|i| i % 37).collect();
+        items.shuffle(&mut rand::thread_rng());
+        for k in 0..items.len() {
+            check_selection(&items, k, SelectionMode::Quickselect);
+            check_selection(&items, k, SelectionMode::MedianOfMedians);
+        }
+    }
+
+    #[test]
+    fn test_select_nth_on_large_inputs() {
+        let mut generator = rand::thread_rng();
+        let mut items: Vec<i32> = (0..5000).collect();
+        items.shuffle(&mut generator);
+        for k in [0, 1, 2499, 4998, 4999] {
+            check_selection(&items, k, SelectionMode::Quickselect);
+            check_selection(&items, k, SelectionMode::MedianOfMedians);
+        }
+        check_selection(&[7; 1000], 500, SelectionMode::MedianOfMedians);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_select_nth_out_of_bounds() {
+        select_nth(&mut [1, 2, 3], 3);
+    }
+
+    #[test]
+    fn test_select_nth_by() {
+        let mut items = ["pear", "fig", "banana", "kiwi"];
+        let longest = select_nth_by(&mut items, 3, |a, b| a.len().cmp(&b.len()));
+        assert_eq!(*longest, "banana");
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median::<i32>(&mut []), None);
+        assert_eq!(median(&mut [3]), Some(&mut 3));
+        assert_eq!(median(&mut [5, 1, 3]), Some(&mut 3));
+        assert_eq!(median(&mut [4, 1, 3, 2]), Some(&mut 2));
+    }
+
+    #[test]
+    fn test_median_of_medians_is_linear_on_adversarial_input() {
+        let len = 4000;
+        let (_, quickselect_comparisons) = antiqsort(len, |items, compare| {
+            select_impl(
+                items,
+                len / 2,
+                &mut |a, b| compare(a, b) == Ordering::Less,
+                SelectionMode::Quickselect,
+            )
+        });
+        let (_, linear_comparisons) = antiqsort(len, |items, compare| {
+            select_impl(
+                items,
+                len / 2,
+                &mut |a, b| compare(a, b) == Ordering::Less,
+                SelectionMode::MedianOfMedians,
+            )
+        });
+        assert!(quickselect_comparisons > len * len / 16);
+        assert!(linear_comparisons < 40 * len);
+    }
+
+    #[test]
+    fn test_partial_sort() {
+        let mut items: Vec<i32> = (0..300).rev().collect();
+        partial_sort(&mut items, 10);
+        assert_eq!(items[..10], (0..10).collect::<Vec<_>>());
+        let mut rest = items[10..].to_vec();
+        rest.sort();
+        assert_eq!(rest, (10..300).collect::<Vec<_>>());
+
+        let mut items = [3, 1, 2];
+        partial_sort(&mut items, 0);
+        assert_eq!(items, [3, 1, 2]);
+        partial_sort(&mut items, 10);
+        assert_eq!(items, [1, 2, 3]);
+
+        let mut items = [3, 1, 4, 1, 5, 9, 2, 6];
+        partial_sort_by(&mut items, 3, |a, b| b.cmp(a));
+        assert_eq!(items[..3], [9, 6, 5]);
+    }
+
+    #[test]
+    fn test_top_k() {
+        let mut items: Vec<i32> = (0..1000).collect();
+        items.shuffle(&mut rand::thread_rng());
+        assert_eq!(top_k(items.iter().copied(), 5), [999, 998, 997, 996, 995]);
+        assert_eq!(top_k(items.iter().copied(), 0), []);
+        assert_eq!(top_k([2, 7, 1], 5), [7, 2, 1]);
+        assert_eq!(top_k([4, 4, 1, 4], 2), [4, 4]);
+
+        let words = ["pear", "fig", "banana", "kiwis"];
+        assert_eq!(
+            top_k_by(words, 2, |a, b| b.len().cmp(&a.len())),
+            ["fig", "pear"]
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use std::cmp::Ordering;
+use std::iter;
+
+use super::instrumented::{NoProbe, SortProbe};
+
+/// The gaps of Ciura's sequence, which were found experimentally. Longer slices continue it by multiplying
+/// the last gap by 2.25.
+const CIURA_GAPS: [usize; 9] = [1, 4, 10, 23, 57, 132, 301, 701, 1750];
+
+/// The gaps Shellsort insertion sorts the elements with, from the largest to 1
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub enum GapSequence {
+    /// Shell's original halving of the length. Takes quadratic time in the worst case.
+    Shell,
+    /// 1, 4, 13, 40, ..., (3^k - 1) / 2, which takes O(n^(3/2)) time
+    Knuth,
+    /// 1, 8, 23, 77, ..., 4^k + 3 * 2^(k-1) + 1, which takes O(n^(4/3)) time
+    Sedgewick,
+    /// 1, 4, 10, 23, 57, 132, 301, 701, 1750, which is among the fastest known sequences in practice
+    #[default]
+    Ciura,
+    /// 1, 4, 9, 20, 46, 103, ..., ceil((9^k - 4^k) / (5 * 4^(k-1)))
+    Tokuda,
+}
+
+impl GapSequence {
+    /// Returns the gaps that are used for sorting `len` elements in ascending order. All gaps are less
+    /// than `len`, so fewer than two elements need no gaps at all.
+    pub fn gaps(self, len: usize) -> Vec<usize> {
+        if self == GapSequence::Shell {
+            let mut gaps: Vec<usize> = iter::successors(Some(len / 2), |gap| Some(gap / 2))
+                .take_while(|gap| *gap > 0)
+                .collect();
+            gaps.reverse();
+            return gaps;
+        }
+        (0..)
+            .map(|k| self.gap(k))
+            .take_while(|gap| *gap < len)
+            .collect()
+    }
+
+    /// Returns the `k`th gap of the sequences that do not depend on the length
+    fn gap(self, k: u32) -> usize {
+        match self {
+            GapSequence::Shell => unreachable!("Shell's gaps depend on the length"),
+            GapSequence::Knuth => (3usize.pow(k + 1) - 1) / 2,
+            GapSequence::Sedgewick if k == 0 => 1,
+            GapSequence::Sedgewick => 4usize.pow(k) + 3 * 2usize.pow(k - 1) + 1,
+            GapSequence::Ciura => match CIURA_GAPS.get(k as usize) {
+                Some(gap) => *gap,
+                None => {
+                    let last = CIURA_GAPS.len() as i32 - 1;
+                    (CIURA_GAPS[last as usize] as f64 * 2.25f64.powi(k as i32 - last)) as usize
+                }
+            },
+            GapSequence::Tokuda => ((9.0 * 2.25f64.powi(k as i32) - 4.0) / 5.0).ceil() as usize,
+        }
+    }
+}
+
+/// Sorts the elements with Shellsort and Ciura's gaps. Insertion sorts the elements that are a gap apart for
+/// shrinking gaps, which moves elements far in few steps. Not stable.
+pub fn shellsort<T: PartialOrd>(elements: &mut [T]) {
+    shellsort_with(elements, GapSequence::default());
+}
+
+/// Like `shellsort`, but with the gaps of `gaps`
+pub fn shellsort_with<T: PartialOrd>(elements: &mut [T], gaps: GapSequence) {
+    shellsort_impl(elements, &mut |a: &T, b: &T| a < b, gaps, &mut NoProbe);
+}
+
+/// Like `shellsort`, but with a comparator function
+pub fn shellsort_by<T, F: FnMut(&T, &T) -> Ordering>(elements: &mut [T], compare: F) {
+    shellsort_by_with(elements, GapSequence::default(), compare);
+}
+
+/// Like `shellsort_with`, but with a comparator function
+pub fn shellsort_by_with<T, F: FnMut(&T, &T) -> Ordering>(
+    elements: &mut [T],
+    gaps: GapSequence,
+    mut compare: F,
+) {
+    shellsort_impl(
+        elements,
+        &mut |a: &T, b: &T| compare(a, b) == Ordering::Less,
+        gaps,
+        &mut NoProbe,
+    );
+}
+
+pub(crate) fn shellsort_impl<T, F: FnMut(&T, &T) -> bool, P: SortProbe>(
+    elements: &mut [T],
+    is_less: &mut F,
+    gaps: GapSequence,
+    probe: &mut P,
+) {
+    for gap in gaps.gaps(elements.len()).into_iter().rev() {
+        for i in gap..elements.len() {
+            let mut j = i;
+            while j >= gap && is_less(&elements[j], &elements[j - gap]) {
+                elements.swap(j, j - gap);
+                probe.swapped(elements, j, j - gap);
+                j -= gap;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sorting::test_helpers::{self, test_panic_safety};
+
+    use super::*;
+
+    test_helpers::basic_sorting_tests!(shellsort);
+
+    const ALL_SEQUENCES: [GapSequence; 5] = [
+        GapSequence::Shell,
+        GapSequence::Knuth,
+        GapSequence::Sedgewick,
+        GapSequence::Ciura,
+        GapSequence::Tokuda,
+    ];
+
+    #[test]
+    fn test_gap_sequences() {
+        assert_eq!(GapSequence::Shell.gaps(100), [1, 3, 6, 12, 25, 50]);
+        assert_eq!(GapSequence::Knuth.gaps(100), [1, 4, 13, 40]);
+        assert_eq!(GapSequence::Sedgewick.gaps(300), [1, 8, 23, 77, 281]);
+        assert_eq!(GapSequence::Ciura.gaps(100), [1, 4, 10, 23, 57]);
+        assert_eq!(
+            GapSequence::Ciura.gaps(10_000),
+            [1, 4, 10, 23, 57, 132, 301, 701, 1750, 3937, 8859]
+        );
+        assert_eq!(
+            GapSequence::Tokuda.gaps(600),
+            [1, 4, 9, 20, 46, 103, 233, 525]
+        );
+        for sequence in ALL_SEQUENCES {
+            assert!(sequence.gaps(0).is_empty());
+            assert!(sequence.gaps(1).is_empty());
+            assert_eq!(sequence.gaps(2), [1]);
+        }
+    }
+
+    #[test]
+    fn test_shellsort_conformance() {
+        for sequence in ALL_SEQUENCES {
+            test_helpers::test_sort_conformance(|items: &mut [u32]| {
+                shellsort_with(items, sequence)
+            });
+            test_helpers::test_sort_conformance(|items: &mut [f64]| {
+                shellsort_by_with(items, sequence, |a, b| a.partial_cmp(b).unwrap())
+            });
+        }
+    }
+
+    #[test]
+    fn test_shellsort_by() {
+        let mut data = [3, 1, 4, 1, 5, 9, 2, 6];
+        shellsort_by(&mut data, |a, b| b.cmp(a));
+        assert_eq!(data, [9, 6, 5, 4, 3, 2, 1, 1]);
+    }
+
+    #[test]
+    fn test_shellsort_is_panic_safe() {
+        test_panic_safety(shellsort, 30);
+        test_panic_safety(|items| shellsort_with(items, GapSequence::Shell), 30);
+    }
+}
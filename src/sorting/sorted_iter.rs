@@ -0,0 +1,445 @@
+use std::cmp::Ordering;
+use std::iter::{FusedIterator, Peekable};
+
+use crate::collections::sift_down;
+
+/// Returns true if no item is less than the one before it. Incomparable items count as equal.
+pub fn is_sorted<I: IntoIterator>(items: I) -> bool
+where
+    I::Item: PartialOrd,
+{
+    is_sorted_by(items, |a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+}
+
+/// Returns true if `compare` never finds an item to be greater than the one after it
+pub fn is_sorted_by<I: IntoIterator, F: FnMut(&I::Item, &I::Item) -> Ordering>(
+    items: I,
+    mut compare: F,
+) -> bool {
+    let mut items = items.into_iter();
+    let Some(mut last) = items.next() else {
+        return true;
+    };
+    for item in items {
+        if compare(&last, &item) == Ordering::Greater {
+            return false;
+        }
+        last = item;
+    }
+    true
+}
+
+/// Merges sorted iterators into one sorted iterator, see `merge_sorted_by`
+pub fn merge_sorted<I: IntoIterator>(
+    iterators: I,
+) -> MergeSorted<Source<I>, impl FnMut(&Item<I>, &Item<I>) -> Ordering>
+where
+    I::Item: IntoIterator,
+    Item<I>: PartialOrd,
+{
+    merge_sorted_by(iterators, |a: &Item<I>, b: &Item<I>| {
+        a.partial_cmp(b).unwrap_or(Ordering::Equal)
+    })
+}
+
+/// The iterators `merge_sorted` merges
+type Source<I> = <<I as IntoIterator>::Item as IntoIterator>::IntoIter;
+
+/// The items of the iterators `merge_sorted` merges
+type Item<I> = <Source<I> as Iterator>::Item;
+
+/// Merges iterators that are sorted by `compare` into one sorted iterator. Keeps the next item of every
+/// iterator in a heap, so each item takes O(log k) comparisons for k iterators. The merge is lazy, it
+/// only takes the next item of an iterator once the previous one was returned.
+/// Equal items are returned in the order of their iterators, and in their order within an iterator.
+pub fn merge_sorted_by<I, F>(iterators: I, compare: F) -> MergeSorted<Source<I>, F>
+where
+    I: IntoIterator,
+    I::Item: IntoIterator,
+    F: FnMut(&Item<I>, &Item<I>) -> Ordering,
+{
+    let mut merge = MergeSorted {
+        sources: iterators.into_iter().map(IntoIterator::into_iter).collect(),
+        heap: Vec::new(),
+        compare,
+    };
+    for source in (0..merge.sources.len()).rev() {
+        if let Some(item) = merge.sources[source].next() {
+            merge.heap.push((item, source));
+        }
+    }
+    for node in (0..merge.heap.len() / 2).rev() {
+        merge.sift_down(node);
+    }
+    merge
+}
+
+/// The iterator returned by `merge_sorted` and `merge_sorted_by`
+pub struct MergeSorted<I: Iterator, F> {
+    sources: Vec<I>,
+    /// The next item of every iterator that is not exhausted, with the index of the iterator.
+    /// The item that has to be returned next is at the root.
+    heap: Vec<(I::Item, usize)>,
+    compare: F,
+}
+
+impl<I: Iterator, F: FnMut(&I::Item, &I::Item) -> Ordering> MergeSorted<I, F> {
+    fn sift_down(&mut self, node: usize) {
+        let compare = &mut self.compare;
+        // the heap puts the greatest element at the root, so later items have to be less
+        sift_down(
+            &mut self.heap,
+            node,
+            &mut |a: &(I::Item, usize), b: &(I::Item, usize)| match compare(&a.0, &b.0) {
+                Ordering::Equal => a.1 > b.1,
+                ordering => ordering == Ordering::Greater,
+            },
+            &mut |heap: &mut [(I::Item, usize)], a, b| heap.swap(a, b),
+        );
+    }
+}
+
+impl<I: Iterator, F: FnMut(&I::Item, &I::Item) -> Ordering> Iterator for MergeSorted<I, F> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let source = self.heap.first()?.1;
+        let item = match self.sources[source].next() {
+            Some(next) => std::mem::replace(&mut self.heap[0], (next, source)).0,
+            None => self.heap.swap_remove(0).0,
+        };
+        if !self.heap.is_empty() {
+            self.sift_down(0);
+        }
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.sources.iter().map(Iterator::size_hint).fold(
+            (self.heap.len(), Some(self.heap.len())),
+            |sum, hint| {
+                (
+                    sum.0.saturating_add(hint.0),
+                    sum.1.zip(hint.1).and_then(|(a, b)| a.checked_add(b)),
+                )
+            },
+        )
+    }
+}
+
+// an iterator that returned None is never asked again
+impl<I: Iterator, F: FnMut(&I::Item, &I::Item) -> Ordering> FusedIterator for MergeSorted<I, F> {}
+
+/// Removes consecutive equal items from a sorted iterator, keeping the first one of each run.
+/// Incomparable items count as equal.
+pub fn dedup_sorted<I: IntoIterator>(items: I) -> DedupSorted<I::IntoIter>
+where
+    I::Item: PartialOrd,
+{
+    DedupSorted {
+        items: items.into_iter().peekable(),
+    }
+}
+
+/// The iterator returned by `dedup_sorted`
+pub struct DedupSorted<I: Iterator> {
+    items: Peekable<I>,
+}
+
+impl<I: Iterator> Iterator for DedupSorted<I>
+where
+    I::Item: PartialOrd,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let item = self.items.next()?;
+        while self
+            .items
+            .next_if(|next| item.partial_cmp(next) != Some(Ordering::Less))
+            .is_some()
+        {}
+        Some(item)
+    }
+}
+
+/// Returns the items of the sorted iterator `a` that are also in the sorted iterator `b`. An item that
+/// occurs several times is returned as often as it occurs in both.
+pub fn intersect_sorted<A, B>(a: A, b: B) -> IntersectSorted<A::IntoIter, B::IntoIter>
+where
+    A: IntoIterator,
+    B: IntoIterator<Item = A::Item>,
+    A::Item: PartialOrd,
+{
+    IntersectSorted(SortedPair::new(a, b))
+}
+
+/// Returns the items of the sorted iterators `a` and `b` in sorted order. An item that occurs several
+/// times is returned as often as it occurs in the one with more of it, taking equal items from `a` first.
+pub fn union_sorted<A, B>(a: A, b: B) -> UnionSorted<A::IntoIter, B::IntoIter>
+where
+    A: IntoIterator,
+    B: IntoIterator<Item = A::Item>,
+    A::Item: PartialOrd,
+{
+    UnionSorted(SortedPair::new(a, b))
+}
+
+/// Returns the items of the sorted iterator `a` that are not in the sorted iterator `b`. An item that
+/// occurs several times is returned as often as it occurs more often in `a` than in `b`.
+pub fn difference_sorted<A, B>(a: A, b: B) -> DifferenceSorted<A::IntoIter, B::IntoIter>
+where
+    A: IntoIterator,
+    B: IntoIterator<Item = A::Item>,
+    A::Item: PartialOrd,
+{
+    DifferenceSorted(SortedPair::new(a, b))
+}
+
+/// The iterator returned by `intersect_sorted`
+pub struct IntersectSorted<A: Iterator, B: Iterator>(SortedPair<A, B>);
+
+/// The iterator returned by `union_sorted`
+pub struct UnionSorted<A: Iterator, B: Iterator>(SortedPair<A, B>);
+
+/// The iterator returned by `difference_sorted`
+pub struct DifferenceSorted<A: Iterator, B: Iterator>(SortedPair<A, B>);
+
+/// Two sorted iterators that are walked through together by the set operations
+struct SortedPair<A: Iterator, B: Iterator> {
+    a: Peekable<A>,
+    b: Peekable<B>,
+}
+
+impl<A: Iterator, B: Iterator<Item = A::Item>> SortedPair<A, B>
+where
+    A::Item: PartialOrd,
+{
+    fn new<IA, IB>(a: IA, b: IB) -> Self
+    where
+        IA: IntoIterator<IntoIter = A>,
+        IB: IntoIterator<IntoIter = B>,
+    {
+        SortedPair {
+            a: a.into_iter().peekable(),
+            b: b.into_iter().peekable(),
+        }
+    }
+
+    /// Compares the next items of both iterators, None if one of them is exhausted.
+    /// Incomparable items count as equal.
+    fn compare_next(&mut self) -> Option<Ordering> {
+        let (a, b) = (self.a.peek()?, self.b.peek()?);
+        Some(a.partial_cmp(b).unwrap_or(Ordering::Equal))
+    }
+}
+
+impl<A: Iterator, B: Iterator<Item = A::Item>> Iterator for IntersectSorted<A, B>
+where
+    A::Item: PartialOrd,
+{
+    type Item = A::Item;
+
+    fn next(&mut self) -> Option<A::Item> {
+        loop {
+            match self.0.compare_next()? {
+                Ordering::Less => {
+                    self.0.a.next();
+                }
+                Ordering::Greater => {
+                    self.0.b.next();
+                }
+                Ordering::Equal => {
+                    self.0.b.next();
+                    return self.0.a.next();
+                }
+            }
+        }
+    }
+}
+
+impl<A: Iterator, B: Iterator<Item = A::Item>> Iterator for UnionSorted<A, B>
+where
+    A::Item: PartialOrd,
+{
+    type Item = A::Item;
+
+    fn next(&mut self) -> Option<A::Item> {
+        match self.0.compare_next() {
+            Some(Ordering::Greater) => self.0.b.next(),
+            Some(Ordering::Equal) => {
+                self.0.b.next();
+                self.0.a.next()
+            }
+            Some(Ordering::Less) => self.0.a.next(),
+            None => self.0.a.next().or_else(|| self.0.b.next()),
+        }
+    }
+}
+
+impl<A: Iterator, B: Iterator<Item = A::Item>> Iterator for DifferenceSorted<A, B>
+where
+    A::Item: PartialOrd,
+{
+    type Item = A::Item;
+
+    fn next(&mut self) -> Option<A::Item> {
+        loop {
+            match self.0.compare_next() {
+                Some(Ordering::Less) | None => return self.0.a.next(),
+                Some(Ordering::Greater) => {
+                    self.0.b.next();
+                }
+                Some(Ordering::Equal) => {
+                    self.0.a.next();
+                    self.0.b.next();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use rand::Rng;
+
+    use super::*;
+
+    fn random_sorted_lists(count: usize, max_len: usize, max_value: u32) -> Vec<Vec<u32>> {
+        let mut generator = rand::thread_rng();
+        (0..count)
+            .map(|_| {
+                let len = generator.gen_range(0..=max_len);
+                let mut list: Vec<u32> = (0..len)
+                    .map(|_| generator.gen_range(0..max_value))
+                    .collect();
+                list.sort();
+                list
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_is_sorted() {
+        assert!(is_sorted(Vec::<i32>::new()));
+        assert!(is_sorted([1]));
+        assert!(is_sorted([1, 1, 2, 5]));
+        assert!(!is_sorted([1, 3, 2]));
+        assert!(is_sorted_by([5, 3, 3, 1], |a, b| b.cmp(a)));
+        assert!(!is_sorted_by(["bb", "a"], |a, b| a.cmp(b)));
+        assert!(is_sorted_by(["bb", "a"], |a, b| b.len().cmp(&a.len())));
+    }
+
+    #[test]
+    fn test_merge_sorted() {
+        let lists = random_sorted_lists(20, 100, 50);
+        let merged: Vec<u32> =
+            merge_sorted(lists.iter().map(|list| list.iter().copied())).collect();
+        let mut expected: Vec<u32> = lists.into_iter().flatten().collect();
+        expected.sort();
+        assert_eq!(merged, expected);
+
+        assert_eq!(merge_sorted(Vec::<Vec<u32>>::new()).count(), 0);
+        assert_eq!(
+            merge_sorted([vec![], vec![1, 2], vec![]]).collect::<Vec<_>>(),
+            [1, 2]
+        );
+    }
+
+    #[test]
+    fn test_merge_sorted_is_stable_by_source_order() {
+        let shards = [
+            vec![(1, "a0"), (3, "a1")],
+            vec![(1, "b0"), (1, "b1"), (2, "b2")],
+            vec![(0, "c0"), (3, "c1")],
+        ];
+        let merged: Vec<&str> = merge_sorted_by(shards, |a, b| a.0.cmp(&b.0))
+            .map(|(_, name)| name)
+            .collect();
+        assert_eq!(merged, ["c0", "a0", "b0", "b1", "b2", "a1", "c1"]);
+    }
+
+    #[test]
+    fn test_merge_sorted_is_lazy() {
+        let pulled = Cell::new(0);
+        let counted = |start: u32| {
+            (start..)
+                .step_by(2)
+                .inspect(|_| pulled.set(pulled.get() + 1))
+        };
+        // both iterators are endless
+        let first: Vec<u32> = merge_sorted([counted(0), counted(1)]).take(5).collect();
+        assert_eq!(first, [0, 1, 2, 3, 4]);
+        // one item of each iterator to start with and one more for every item that was returned
+        assert_eq!(pulled.get(), 2 + 5);
+    }
+
+    #[test]
+    fn test_merge_sorted_size_hint() {
+        let merge = merge_sorted([vec![1, 4], vec![2, 3, 5]]);
+        assert_eq!(merge.size_hint(), (5, Some(5)));
+        assert_eq!(merge.skip(2).size_hint(), (3, Some(3)));
+    }
+
+    #[test]
+    fn test_dedup_sorted() {
+        assert_eq!(
+            dedup_sorted([1, 1, 2, 3, 3, 3, 7]).collect::<Vec<_>>(),
+            [1, 2, 3, 7]
+        );
+        assert_eq!(dedup_sorted(Vec::<i32>::new()).count(), 0);
+        let names = ["Anna", "anna", "Bob", "bob", "Carl"];
+        let keys = dedup_sorted(names.iter().map(|name| name.to_lowercase()));
+        assert_eq!(keys.collect::<Vec<_>>(), ["anna", "bob", "carl"]);
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = [1, 2, 2, 2, 4, 6, 9];
+        let b = [2, 2, 3, 4, 9, 9, 10];
+        assert_eq!(intersect_sorted(a, b).collect::<Vec<_>>(), [2, 2, 4, 9]);
+        assert_eq!(
+            union_sorted(a, b).collect::<Vec<_>>(),
+            [1, 2, 2, 2, 3, 4, 6, 9, 9, 10]
+        );
+        assert_eq!(difference_sorted(a, b).collect::<Vec<_>>(), [1, 2, 6]);
+        assert_eq!(difference_sorted(b, a).collect::<Vec<_>>(), [3, 9, 10]);
+        assert_eq!(intersect_sorted(a, []).count(), 0);
+        assert_eq!(union_sorted([], b).collect::<Vec<_>>(), b);
+    }
+
+    #[test]
+    fn test_set_operations_on_random_sets() {
+        for lists in (0..20).map(|_| random_sorted_lists(2, 50, 30)) {
+            let a: Vec<u32> = dedup_sorted(lists[0].iter().copied()).collect();
+            let b: Vec<u32> = dedup_sorted(lists[1].iter().copied()).collect();
+            let in_both: Vec<u32> = a.iter().copied().filter(|x| b.contains(x)).collect();
+            let only_a: Vec<u32> = a.iter().copied().filter(|x| !b.contains(x)).collect();
+            let mut either: Vec<u32> = a.iter().chain(&b).copied().collect();
+            either.sort();
+            either.dedup();
+
+            assert_eq!(
+                intersect_sorted(a.iter(), b.iter())
+                    .copied()
+                    .collect::<Vec<_>>(),
+                in_both
+            );
+            assert_eq!(
+                difference_sorted(a.iter(), b.iter())
+                    .copied()
+                    .collect::<Vec<_>>(),
+                only_a
+            );
+            assert_eq!(
+                union_sorted(a.iter(), b.iter())
+                    .copied()
+                    .collect::<Vec<_>>(),
+                either
+            );
+            assert!(is_sorted(union_sorted(a.iter(), b.iter())));
+        }
+    }
+}
@@ -1,3 +1,8 @@
+use std::cell::{Cell, RefCell};
+use std::cmp::Ordering;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+
 use rand::Rng;
 
 extern crate rand;
@@ -5,11 +10,24 @@ extern crate rand;
 /// This represents an item that secretly holds information about its initial position in the array.
 /// During the sort operation, it will only compare the value held. Later after the algorithm has run,
 /// the initial index can be used to identify if the value was sorted in a stable manner.
+#[derive(Clone, Debug)]
 pub struct StableSortComparableItem {
     value: i64,
     initial_index: usize,
 }
 
+impl StableSortComparableItem {
+    /// The value the item is compared by
+    pub fn value(&self) -> i64 {
+        self.value
+    }
+
+    /// The position of the item in the generated list
+    pub fn initial_index(&self) -> usize {
+        self.initial_index
+    }
+}
+
 impl PartialEq for StableSortComparableItem {
     fn eq(&self, other: &Self) -> bool {
         self.value == other.value
@@ -19,16 +37,15 @@ impl PartialEq for StableSortComparableItem {
 impl PartialOrd for StableSortComparableItem {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         match self.value.partial_cmp(&other.value) {
-            Some(core::cmp::Ordering::Equal) => return Some(core::cmp::Ordering::Equal),
-            ord => return ord,
+            Some(core::cmp::Ordering::Equal) => Some(core::cmp::Ordering::Equal),
+            ord => ord,
         }
     }
 }
 
 /// Generates a random list of StableSortComparableItems with the correct initial index
 pub fn random_comparable_list(len: usize, min: i64, max: i64) -> Vec<StableSortComparableItem> {
-    let mut result = Vec::new();
-    result.reserve(len);
+    let mut result = Vec::with_capacity(len);
     let mut generator = rand::thread_rng();
 
     for i in 0..len {
@@ -42,33 +59,13 @@ pub fn random_comparable_list(len: usize, min: i64, max: i64) -> Vec<StableSortC
 
 /// this function allows you to check if a slice of StableSortComparableItems was actually sorted in a stable manner
 pub fn is_stabelly_sorted(items: &[StableSortComparableItem]) -> bool {
-    for i in 0..(items.len() - 1) {
-        if items[i].value > items[i + 1].value {
-            return false;
-        }
-        if items[i].value == items[i + 1].value
-            && items[i].initial_index > items[i + 1].initial_index
-        {
-            return false;
-        }
-    }
-    return true;
+    items.windows(2).all(|pair| {
+        pair[0].value < pair[1].value
+            || (pair[0].value == pair[1].value && pair[0].initial_index < pair[1].initial_index)
+    })
 }
 
-pub fn is_sorted<T: PartialOrd, I: Iterator<Item = T>>(mut items: I) -> bool {
-    let last = items.next();
-    if last.is_none() {
-        return true;
-    }
-
-    let last = last.unwrap();
-    while let Some(item) = items.next() {
-        if last > item {
-            return false;
-        }
-    }
-    return true;
-}
+pub use super::sorted_iter::is_sorted;
 
 // pub fn test_basic_sort_functions<F: Fn(&mut [i32]) -> ()>(fun: &F) {
 //     it_sorts_empty_slices(&fun);
@@ -76,6 +73,8 @@ pub fn is_sorted<T: PartialOrd, I: Iterator<Item = T>>(mut items: I) -> bool {
 //     it_sorts_element_slices_of_same_elements(&fun);
 // }
 
+/// Generates tests that sort empty slices, slices with one element and slices of equal elements of `i32`
+#[macro_export]
 macro_rules! basic_sorting_tests {
     ($fnname: ident) => {
         #[test]
@@ -98,14 +97,9 @@ macro_rules! basic_sorting_tests {
     };
 }
 
-pub(crate) use basic_sorting_tests;
+pub use crate::basic_sorting_tests;
 
-pub fn test_unstable_sort<F: Fn(&mut [i32]) -> ()>(
-    fun: &F,
-    num_elements: usize,
-    min: i32,
-    max: i32,
-) {
+pub fn test_unstable_sort<F: Fn(&mut [i32])>(fun: &F, num_elements: usize, min: i32, max: i32) {
     let generator = rand::thread_rng();
     let mut items: Vec<_> = generator
         .sample_iter(rand::distributions::Uniform::new(min, max))
@@ -115,3 +109,395 @@ pub fn test_unstable_sort<F: Fn(&mut [i32]) -> ()>(
     fun(&mut items);
     assert!(is_sorted(items.iter()));
 }
+
+/// McIlroy's "killer adversary for quicksort". Runs `sort` on the items `0..len` with a comparator that
+/// decides the relative order of the items lazily, always in the way that hurts the sort the most.
+/// Returns the input that makes `sort` behave exactly like this and the number of comparisons it needed.
+pub fn antiqsort<S>(len: usize, sort: S) -> (Vec<usize>, usize)
+where
+    S: FnOnce(&mut [usize], &mut dyn FnMut(&usize, &usize) -> Ordering),
+{
+    struct Adversary {
+        values: Vec<usize>,
+        gas: usize,
+        num_solid: usize,
+        candidate: usize,
+        comparisons: usize,
+    }
+
+    impl Adversary {
+        fn freeze(&mut self, item: usize) {
+            self.values[item] = self.num_solid;
+            self.num_solid += 1;
+        }
+    }
+
+    let adversary = RefCell::new(Adversary {
+        values: vec![len; len],
+        gas: len,
+        num_solid: 0,
+        candidate: 0,
+        comparisons: 0,
+    });
+
+    let mut compare = |x: &usize, y: &usize| {
+        let adversary = &mut *adversary.borrow_mut();
+        let (x, y) = (*x, *y);
+        adversary.comparisons += 1;
+        if adversary.values[x] == adversary.gas && adversary.values[y] == adversary.gas {
+            if x == adversary.candidate {
+                adversary.freeze(x);
+            } else {
+                adversary.freeze(y);
+            }
+        }
+        if adversary.values[x] == adversary.gas {
+            adversary.candidate = x;
+        } else if adversary.values[y] == adversary.gas {
+            adversary.candidate = y;
+        }
+        adversary.values[x].cmp(&adversary.values[y])
+    };
+
+    let mut items: Vec<usize> = (0..len).collect();
+    sort(&mut items, &mut compare);
+
+    let mut adversary = adversary.into_inner();
+    for item in 0..len {
+        if adversary.values[item] == adversary.gas {
+            adversary.freeze(item);
+        }
+    }
+    (adversary.values, adversary.comparisons)
+}
+
+thread_local! {
+    static COMPARISONS_UNTIL_PANIC: Cell<usize> = const { Cell::new(usize::MAX) };
+}
+
+/// An item that counts how often it is dropped and panics when it is compared after the budget set
+/// by `test_panic_safety` is used up
+pub struct PanickingItem {
+    value: i64,
+    id: usize,
+    drops: Rc<Vec<Cell<usize>>>,
+}
+
+impl PanickingItem {
+    fn count_comparison() {
+        COMPARISONS_UNTIL_PANIC.with(|left| {
+            if left.get() == 0 {
+                panic!("injected panic during comparison");
+            }
+            left.set(left.get() - 1);
+        });
+    }
+}
+
+impl PartialEq for PanickingItem {
+    fn eq(&self, other: &Self) -> bool {
+        PanickingItem::count_comparison();
+        self.value == other.value
+    }
+}
+
+impl PartialOrd for PanickingItem {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        PanickingItem::count_comparison();
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl Drop for PanickingItem {
+    fn drop(&mut self) {
+        let drops = &self.drops[self.id];
+        drops.set(drops.get() + 1);
+    }
+}
+
+fn panicking_items(values: &[i64]) -> (Vec<PanickingItem>, Rc<Vec<Cell<usize>>>) {
+    let drops = Rc::new(values.iter().map(|_| Cell::new(0)).collect::<Vec<_>>());
+    let items = values
+        .iter()
+        .enumerate()
+        .map(|(id, value)| PanickingItem {
+            value: *value,
+            id,
+            drops: drops.clone(),
+        })
+        .collect();
+    (items, drops)
+}
+
+/// Makes the n-th comparison of `sort` panic, for every n the sort reaches on a random input of `len`
+/// items. After every panic the slice has to still be a permutation of the input, and every item has to
+/// be dropped exactly once afterwards.
+pub fn test_panic_safety<S: Fn(&mut [PanickingItem])>(sort: S, len: usize) {
+    let mut generator = rand::thread_rng();
+    let values: Vec<i64> = (0..len)
+        .map(|_| generator.gen_range(0..=len as i64 / 2))
+        .collect();
+
+    let (mut items, _) = panicking_items(&values);
+    sort(&mut items);
+    let comparisons = usize::MAX - COMPARISONS_UNTIL_PANIC.with(|left| left.replace(usize::MAX));
+
+    for panic_at in 0..comparisons {
+        let (mut items, drops) = panicking_items(&values);
+        COMPARISONS_UNTIL_PANIC.with(|left| left.set(panic_at));
+        let result = panic::catch_unwind(AssertUnwindSafe(|| sort(&mut items)));
+        COMPARISONS_UNTIL_PANIC.with(|left| left.set(usize::MAX));
+        assert!(result.is_err(), "comparison {} did not panic", panic_at);
+
+        let mut ids: Vec<usize> = items.iter().map(|item| item.id).collect();
+        ids.sort();
+        assert_eq!(
+            ids,
+            (0..len).collect::<Vec<_>>(),
+            "items were duplicated or lost"
+        );
+        assert!(
+            drops.iter().all(|count| count.get() == 0),
+            "items were dropped during the sort"
+        );
+
+        drop(items);
+        assert!(
+            drops.iter().all(|count| count.get() == 1),
+            "items were not dropped exactly once"
+        );
+    }
+}
+
+/// The kinds of inputs the conformance suite sorts
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum InputShape {
+    /// Every element is equal
+    AllEqual,
+    /// Only a handful of distinct values
+    DuplicateHeavy,
+    /// Already sorted
+    Presorted,
+    /// Sorted in descending order
+    Reversed,
+    /// Ascending up to the middle, then descending
+    OrganPipe,
+    /// Several ascending runs of the same length
+    Sawtooth,
+    /// Uniformly random values
+    Random,
+    /// Musser's input that makes quicksort with a median of three pivot quadratic
+    MedianOfThreeKiller,
+    /// Sorted, except for a few elements that were swapped with random other ones
+    NearlySorted,
+    /// Sorted, followed by a short tail of random values
+    RandomTail,
+}
+
+impl InputShape {
+    pub const ALL: [InputShape; 10] = [
+        InputShape::AllEqual,
+        InputShape::DuplicateHeavy,
+        InputShape::Presorted,
+        InputShape::Reversed,
+        InputShape::OrganPipe,
+        InputShape::Sawtooth,
+        InputShape::Random,
+        InputShape::MedianOfThreeKiller,
+        InputShape::NearlySorted,
+        InputShape::RandomTail,
+    ];
+
+    /// Generates `len` ranks of this shape. Equal ranks stand for equal elements.
+    pub fn generate(self, len: usize) -> Vec<usize> {
+        let mut generator = rand::thread_rng();
+        match self {
+            InputShape::AllEqual => vec![0; len],
+            InputShape::DuplicateHeavy => (0..len).map(|_| generator.gen_range(0..4)).collect(),
+            InputShape::Presorted => (0..len).collect(),
+            InputShape::Reversed => (0..len).rev().collect(),
+            InputShape::OrganPipe => (0..len).map(|i| usize::min(i, len - i)).collect(),
+            InputShape::Sawtooth => {
+                let tooth = usize::max(len / 5, 1);
+                (0..len).map(|i| i % tooth).collect()
+            }
+            InputShape::Random => (0..len).map(|_| generator.gen_range(0..len)).collect(),
+            InputShape::MedianOfThreeKiller => median_of_three_killer(len),
+            InputShape::NearlySorted => {
+                let mut ranks: Vec<usize> = (0..len).collect();
+                if len > 0 {
+                    for _ in 0..len / 32 + 1 {
+                        ranks.swap(generator.gen_range(0..len), generator.gen_range(0..len));
+                    }
+                }
+                ranks
+            }
+            InputShape::RandomTail => {
+                let sorted = len - len / 8;
+                (0..sorted)
+                    .chain((sorted..len).map(|_| generator.gen_range(0..len)))
+                    .collect()
+            }
+        }
+    }
+}
+
+fn median_of_three_killer(len: usize) -> Vec<usize> {
+    // the construction needs an even half, the elements left over are appended in order
+    let half = (len / 2) & !1;
+    let mut ranks: Vec<usize> = (1..=len).collect();
+    for i in 1..=half {
+        if i % 2 == 1 {
+            ranks[i - 1] = i;
+            ranks[i] = half + i;
+        }
+        ranks[half + i - 1] = 2 * i;
+    }
+    ranks
+}
+
+/// Values the conformance suite can build from ranks. Greater ranks have to give values that are not less.
+pub trait TestValue: PartialOrd + Clone + std::fmt::Debug {
+    fn from_rank(rank: usize) -> Self;
+}
+
+macro_rules! integer_test_values {
+    ($($t: ty),*) => {$(
+        impl TestValue for $t {
+            fn from_rank(rank: usize) -> Self {
+                <$t>::try_from(rank).unwrap_or(<$t>::MAX)
+            }
+        }
+    )*};
+}
+
+integer_test_values!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl TestValue for f32 {
+    fn from_rank(rank: usize) -> Self {
+        rank as f32
+    }
+}
+
+impl TestValue for f64 {
+    fn from_rank(rank: usize) -> Self {
+        rank as f64
+    }
+}
+
+impl TestValue for String {
+    fn from_rank(rank: usize) -> Self {
+        format!("{:020}", rank)
+    }
+}
+
+/// The lengths every shape is generated with by the conformance suite
+pub const CONFORMANCE_LENGTHS: [usize; 13] = [0, 1, 2, 3, 4, 7, 16, 17, 32, 65, 100, 257, 1000];
+
+/// Runs `sort` on every `InputShape` in every length of `CONFORMANCE_LENGTHS` and checks that the result is
+/// sorted and a permutation of the input. Panics with the shape and length of the first failing input.
+pub fn test_sort_conformance<T: TestValue, S: FnMut(&mut [T])>(mut sort: S) {
+    for shape in InputShape::ALL {
+        for len in CONFORMANCE_LENGTHS {
+            let mut items: Vec<T> = shape.generate(len).into_iter().map(T::from_rank).collect();
+            let mut expected = items.clone();
+            expected.sort_by(|a, b| a.partial_cmp(b).expect("test values have to be comparable"));
+
+            sort(&mut items);
+            assert!(
+                items == expected,
+                "{:?} input of length {} was sorted to {:?}",
+                shape,
+                len,
+                items
+            );
+        }
+    }
+}
+
+/// Like `test_sort_conformance`, but also checks that `sort` keeps equal elements in their order
+pub fn test_stable_sort_conformance<S: FnMut(&mut [StableSortComparableItem])>(mut sort: S) {
+    for shape in InputShape::ALL {
+        for len in CONFORMANCE_LENGTHS {
+            let mut items: Vec<StableSortComparableItem> = shape
+                .generate(len)
+                .into_iter()
+                .enumerate()
+                .map(|(initial_index, rank)| StableSortComparableItem {
+                    value: rank as i64,
+                    initial_index,
+                })
+                .collect();
+
+            sort(&mut items);
+            let mut indices: Vec<usize> = items.iter().map(|item| item.initial_index).collect();
+            indices.sort();
+            assert!(
+                indices.iter().copied().eq(0..len),
+                "{:?} input of length {} lost elements",
+                shape,
+                len
+            );
+            assert!(
+                is_stabelly_sorted(&items),
+                "{:?} input of length {} was not sorted stabelly",
+                shape,
+                len
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shapes_have_the_right_length() {
+        for shape in InputShape::ALL {
+            for len in CONFORMANCE_LENGTHS {
+                assert_eq!(shape.generate(len).len(), len);
+            }
+        }
+    }
+
+    #[test]
+    fn test_median_of_three_killer_is_a_permutation() {
+        for len in [2, 3, 10, 11, 12, 100] {
+            let mut ranks = median_of_three_killer(len);
+            ranks.sort();
+            assert!(ranks.iter().copied().eq(1..=len));
+        }
+    }
+
+    #[test]
+    fn test_is_sorted() {
+        assert!(is_sorted([1, 2, 2, 3].iter()));
+        assert!(!is_sorted([1, 3, 2].iter()));
+        assert!(is_sorted(std::iter::empty::<i32>()));
+        assert!(is_stabelly_sorted(&[]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_conformance_catches_broken_sorts() {
+        test_sort_conformance(|items: &mut [i32]| items.reverse());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_stable_conformance_catches_unstable_sorts() {
+        test_stable_sort_conformance(|items: &mut [StableSortComparableItem]| {
+            items.sort_by_key(|item| item.value);
+            items.reverse();
+            items.sort_by_key(|item| item.value);
+        });
+    }
+
+    #[test]
+    fn test_conformance_of_std_sorts() {
+        test_sort_conformance(<[String]>::sort_unstable);
+        test_sort_conformance(|items: &mut [f64]| items.sort_by(f64::total_cmp));
+        test_stable_sort_conformance(|items| items.sort_by_key(|item| item.value));
+    }
+}
@@ -0,0 +1,596 @@
+use std::cmp::Ordering;
+use std::ptr;
+use std::slice;
+
+use super::insertionsort::binary_insertionsort_impl;
+use super::instrumented::NoProbe;
+use super::mergesort::MergeHole;
+
+/// Runs shorter than this are extended with binary insertion sort. The actual minimum run length is
+/// chosen between MIN_MERGE / 2 and MIN_MERGE so the number of runs is close to a power of two.
+const MIN_MERGE: usize = 64;
+
+/// How many times in a row one run has to win before the merge switches to galloping
+const MIN_GALLOP: usize = 7;
+
+/// Sorts the elements stabelly with Timsort. Already sorted runs, ascending or strictly descending,
+/// are detected and merged, so sorted or reversed inputs only take n - 1 comparisons.
+pub fn timsort<T: PartialOrd>(elements: &mut [T]) {
+    timsort_impl(elements, &mut |a: &T, b: &T| a < b);
+}
+
+/// Sorts the elements stabelly with Timsort and a comparator function.
+/// If `compare` panics, the elements are left in some permutation of the input.
+pub fn timsort_by<T, F: FnMut(&T, &T) -> Ordering>(elements: &mut [T], mut compare: F) {
+    timsort_impl(elements, &mut |a: &T, b: &T| {
+        compare(a, b) == Ordering::Less
+    });
+}
+
+/// Sorts the elements stabelly with Timsort by the key extracted with `key`
+pub fn timsort_by_key<T, K: PartialOrd, F: FnMut(&T) -> K>(elements: &mut [T], mut key: F) {
+    timsort_impl(elements, &mut |a: &T, b: &T| key(a) < key(b));
+}
+
+fn timsort_impl<T, F: FnMut(&T, &T) -> bool>(elements: &mut [T], is_less: &mut F) {
+    let len = elements.len();
+    if len < 2 {
+        return;
+    }
+
+    let min_run = min_run_length(len);
+    let mut sorter = TimSort {
+        is_less,
+        buffer: Vec::with_capacity(len / 2),
+        runs: Vec::new(),
+        min_gallop: MIN_GALLOP,
+    };
+
+    let mut start = 0;
+    while start < len {
+        let mut run_len = make_ascending_run(&mut elements[start..], sorter.is_less);
+        if run_len < min_run {
+            let forced = usize::min(min_run, len - start);
+            binary_insertionsort_impl(
+                &mut elements[start..start + forced],
+                run_len,
+                sorter.is_less,
+                &mut NoProbe,
+            );
+            run_len = forced;
+        }
+        sorter.runs.push(Run {
+            start,
+            len: run_len,
+        });
+        sorter.merge_collapse(elements);
+        start += run_len;
+    }
+    sorter.merge_force_collapse(elements);
+}
+
+/// Returns a length between MIN_MERGE / 2 and MIN_MERGE, such that len / min_run is a power of two or
+/// slightly less than one
+fn min_run_length(mut len: usize) -> usize {
+    let mut remainder = 0;
+    while len >= MIN_MERGE {
+        remainder |= len & 1;
+        len >>= 1;
+    }
+    len + remainder
+}
+
+/// Returns the length of the run at the start of the elements. Strictly descending runs are reversed,
+/// descending runs with equal elements would not be reversed stabelly.
+fn make_ascending_run<T, F: FnMut(&T, &T) -> bool>(elements: &mut [T], is_less: &mut F) -> usize {
+    let len = elements.len();
+    if len < 2 {
+        return len;
+    }
+    let mut end = 2;
+    if is_less(&elements[1], &elements[0]) {
+        while end < len && is_less(&elements[end], &elements[end - 1]) {
+            end += 1;
+        }
+        elements[..end].reverse();
+    } else {
+        while end < len && !is_less(&elements[end], &elements[end - 1]) {
+            end += 1;
+        }
+    }
+    end
+}
+
+/// Returns how many elements of the sorted slice are less than `key`, searching exponentially from `hint`
+fn gallop_left<T, F: FnMut(&T, &T) -> bool>(
+    key: &T,
+    sorted: &[T],
+    hint: usize,
+    is_less: &mut F,
+) -> usize {
+    gallop(sorted, hint, |element| is_less(element, key))
+}
+
+/// Returns how many elements of the sorted slice are not greater than `key`, searching exponentially
+/// from `hint`
+fn gallop_right<T, F: FnMut(&T, &T) -> bool>(
+    key: &T,
+    sorted: &[T],
+    hint: usize,
+    is_less: &mut F,
+) -> usize {
+    gallop(sorted, hint, |element| !is_less(key, element))
+}
+
+/// Finds the partition point of `predicate`, which has to hold for a prefix of the elements.
+/// Probes at hint ± 1, 3, 7, 15, ... first, so it is fast if the answer is close to `hint`.
+fn gallop<T, P: FnMut(&T) -> bool>(elements: &[T], hint: usize, mut predicate: P) -> usize {
+    let (low, high) = if predicate(&elements[hint]) {
+        let mut last = hint;
+        let mut offset = 1;
+        while hint + offset < elements.len() && predicate(&elements[hint + offset]) {
+            last = hint + offset;
+            offset = 2 * offset + 1;
+        }
+        (last + 1, usize::min(hint + offset, elements.len()))
+    } else {
+        let mut last = hint;
+        let mut offset = 1;
+        while offset <= hint && !predicate(&elements[hint - offset]) {
+            last = hint - offset;
+            offset = 2 * offset + 1;
+        }
+        let low = if offset <= hint { hint - offset + 1 } else { 0 };
+        (low, last)
+    };
+    low + elements[low..high].partition_point(predicate)
+}
+
+#[derive(Clone, Copy)]
+struct Run {
+    start: usize,
+    len: usize,
+}
+
+struct TimSort<'a, T, F> {
+    is_less: &'a mut F,
+    buffer: Vec<T>,
+    runs: Vec<Run>,
+    min_gallop: usize,
+}
+
+impl<T, F: FnMut(&T, &T) -> bool> TimSort<'_, T, F> {
+    /// Merges runs until the lengths of the runs on the stack, read from the top, grow at least as fast
+    /// as the fibonacci numbers: runs[i - 2] > runs[i - 1] + runs[i] and runs[i - 1] > runs[i]
+    fn merge_collapse(&mut self, elements: &mut [T]) {
+        while self.runs.len() > 1 {
+            let mut n = self.runs.len() - 2;
+            let len = |i: usize| self.runs[i].len;
+            if (n > 0 && len(n - 1) <= len(n) + len(n + 1))
+                || (n > 1 && len(n - 2) <= len(n - 1) + len(n))
+            {
+                if len(n - 1) < len(n + 1) {
+                    n -= 1;
+                }
+            } else if len(n) > len(n + 1) {
+                break;
+            }
+            self.merge_at(elements, n);
+        }
+    }
+
+    /// Merges all remaining runs once the whole input has been split into runs
+    fn merge_force_collapse(&mut self, elements: &mut [T]) {
+        while self.runs.len() > 1 {
+            let mut n = self.runs.len() - 2;
+            if n > 0 && self.runs[n - 1].len < self.runs[n + 1].len {
+                n -= 1;
+            }
+            self.merge_at(elements, n);
+        }
+    }
+
+    /// Merges the runs at index i and i + 1 of the stack
+    fn merge_at(&mut self, elements: &mut [T], i: usize) {
+        let first = self.runs[i];
+        let second = self.runs[i + 1];
+        self.runs[i].len += second.len;
+        self.runs.remove(i + 1);
+
+        let elements = &mut elements[first.start..second.start + second.len];
+        let is_less = &mut *self.is_less;
+
+        // the elements of the first run that are not greater than the start of the second run,
+        // and the elements of the second run that are not less than the end of the first run,
+        // are already in place
+        let skip = gallop_right(&elements[first.len], &elements[..first.len], 0, is_less);
+        if skip == first.len {
+            return;
+        }
+        let elements = &mut elements[skip..];
+        let len1 = first.len - skip;
+        let len2 = gallop_left(
+            &elements[len1 - 1],
+            &elements[len1..],
+            second.len - 1,
+            is_less,
+        );
+        if len2 == 0 {
+            return;
+        }
+        let elements = &mut elements[..len1 + len2];
+
+        if len1 <= len2 {
+            self.merge_lo(elements, len1);
+        } else {
+            self.merge_hi(elements, len1);
+        }
+    }
+
+    /// Merges the runs `elements[..mid]` and `elements[mid..]` from front to back, with the first run
+    /// moved into the buffer. The first element of the second run has to be less than the first of the
+    /// first run, and the last element of the first run greater than the last of the second run.
+    fn merge_lo(&mut self, elements: &mut [T], mid: usize) {
+        let len = elements.len();
+        let is_less = &mut *self.is_less;
+        let mut min_gallop = self.min_gallop;
+
+        // SAFETY: the gap left by the buffered run always sits right in front of the remaining second run,
+        // and is exactly as long as the part of the first run that is still in the buffer. The slices
+        // created for galloping only cover elements that are not moved while they are alive.
+        // If is_less panics, MergeHole moves the rest of the buffer into the gap.
+        unsafe {
+            let base = elements.as_mut_ptr();
+            let scratch = self.buffer.as_mut_ptr();
+            ptr::copy_nonoverlapping(base, scratch, mid);
+            let mut hole = MergeHole {
+                start: scratch,
+                end: scratch.add(mid),
+                dest: base,
+            };
+            let mut right = base.add(mid);
+            let right_end = base.add(len);
+
+            'merge: loop {
+                let mut wins_left = 0;
+                let mut wins_right = 0;
+
+                // compare one pair at a time until one run keeps winning
+                loop {
+                    if is_less(&*right, &*hole.start) {
+                        ptr::copy_nonoverlapping(right, hole.dest, 1);
+                        hole.dest = hole.dest.add(1);
+                        right = right.add(1);
+                        wins_right += 1;
+                        wins_left = 0;
+                        if right == right_end {
+                            break 'merge;
+                        }
+                    } else {
+                        ptr::copy_nonoverlapping(hole.start, hole.dest, 1);
+                        hole.dest = hole.dest.add(1);
+                        hole.start = hole.start.add(1);
+                        wins_left += 1;
+                        wins_right = 0;
+                        if hole.start == hole.end {
+                            break 'merge;
+                        }
+                    }
+                    if wins_left >= min_gallop || wins_right >= min_gallop {
+                        break;
+                    }
+                }
+
+                // gallop until neither run wins by a large margin anymore
+                loop {
+                    let buffered = slice::from_raw_parts(
+                        hole.start,
+                        hole.end.offset_from(hole.start) as usize,
+                    );
+                    wins_left = gallop_right(&*right, buffered, 0, is_less);
+                    if wins_left > 0 {
+                        ptr::copy_nonoverlapping(hole.start, hole.dest, wins_left);
+                        hole.dest = hole.dest.add(wins_left);
+                        hole.start = hole.start.add(wins_left);
+                        if hole.start == hole.end {
+                            break 'merge;
+                        }
+                    }
+                    ptr::copy_nonoverlapping(right, hole.dest, 1);
+                    hole.dest = hole.dest.add(1);
+                    right = right.add(1);
+                    if right == right_end {
+                        break 'merge;
+                    }
+
+                    let remaining =
+                        slice::from_raw_parts(right, right_end.offset_from(right) as usize);
+                    wins_right = gallop_left(&*hole.start, remaining, 0, is_less);
+                    if wins_right > 0 {
+                        // the gap may be shorter than the block, so the ranges can overlap
+                        ptr::copy(right, hole.dest, wins_right);
+                        hole.dest = hole.dest.add(wins_right);
+                        right = right.add(wins_right);
+                        if right == right_end {
+                            break 'merge;
+                        }
+                    }
+                    ptr::copy_nonoverlapping(hole.start, hole.dest, 1);
+                    hole.dest = hole.dest.add(1);
+                    hole.start = hole.start.add(1);
+                    if hole.start == hole.end {
+                        break 'merge;
+                    }
+
+                    min_gallop = min_gallop.saturating_sub(1);
+                    if wins_left < MIN_GALLOP && wins_right < MIN_GALLOP {
+                        break;
+                    }
+                }
+                // make it harder to start galloping again, since it did not pay off
+                min_gallop += 2;
+            }
+        }
+        self.min_gallop = usize::max(min_gallop, 1);
+    }
+
+    /// Merges the runs `elements[..mid]` and `elements[mid..]` from back to front, with the second run
+    /// moved into the buffer. Has the same requirements as merge_lo.
+    fn merge_hi(&mut self, elements: &mut [T], mid: usize) {
+        let len = elements.len();
+        let is_less = &mut *self.is_less;
+        let mut min_gallop = self.min_gallop;
+
+        // SAFETY: the gap left by the buffered run always sits right behind the remaining first run,
+        // and is exactly as long as the part of the second run that is still in the buffer.
+        // If is_less panics, MergeHole moves the rest of the buffer into the gap.
+        unsafe {
+            let base = elements.as_mut_ptr();
+            let scratch = self.buffer.as_mut_ptr();
+            ptr::copy_nonoverlapping(base.add(mid), scratch, len - mid);
+            let mut hole = MergeHole {
+                start: scratch,
+                end: scratch.add(len - mid),
+                dest: base.add(mid),
+            };
+            let mut out = base.add(len);
+
+            'merge: loop {
+                let mut wins_left = 0;
+                let mut wins_right = 0;
+
+                loop {
+                    let left_last = hole.dest.sub(1);
+                    let right_last = hole.end.sub(1);
+                    if is_less(&*right_last, &*left_last) {
+                        out = out.sub(1);
+                        ptr::copy_nonoverlapping(left_last, out, 1);
+                        hole.dest = left_last;
+                        wins_left += 1;
+                        wins_right = 0;
+                        if hole.dest == base {
+                            break 'merge;
+                        }
+                    } else {
+                        out = out.sub(1);
+                        ptr::copy_nonoverlapping(right_last, out, 1);
+                        hole.end = right_last;
+                        wins_right += 1;
+                        wins_left = 0;
+                        if hole.end == hole.start {
+                            break 'merge;
+                        }
+                    }
+                    if wins_left >= min_gallop || wins_right >= min_gallop {
+                        break;
+                    }
+                }
+
+                loop {
+                    let left_len = hole.dest.offset_from(base) as usize;
+                    let left = slice::from_raw_parts(base, left_len);
+                    wins_left =
+                        left_len - gallop_right(&*hole.end.sub(1), left, left_len - 1, is_less);
+                    if wins_left > 0 {
+                        out = out.sub(wins_left);
+                        hole.dest = hole.dest.sub(wins_left);
+                        // the gap may be shorter than the block, so the ranges can overlap
+                        ptr::copy(hole.dest, out, wins_left);
+                        if hole.dest == base {
+                            break 'merge;
+                        }
+                    }
+                    out = out.sub(1);
+                    hole.end = hole.end.sub(1);
+                    ptr::copy_nonoverlapping(hole.end, out, 1);
+                    if hole.end == hole.start {
+                        break 'merge;
+                    }
+
+                    let right_len = hole.end.offset_from(hole.start) as usize;
+                    let buffered = slice::from_raw_parts(hole.start, right_len);
+                    wins_right = right_len
+                        - gallop_left(&*hole.dest.sub(1), buffered, right_len - 1, is_less);
+                    if wins_right > 0 {
+                        out = out.sub(wins_right);
+                        hole.end = hole.end.sub(wins_right);
+                        ptr::copy_nonoverlapping(hole.end, out, wins_right);
+                        if hole.end == hole.start {
+                            break 'merge;
+                        }
+                    }
+                    out = out.sub(1);
+                    hole.dest = hole.dest.sub(1);
+                    ptr::copy_nonoverlapping(hole.dest, out, 1);
+                    if hole.dest == base {
+                        break 'merge;
+                    }
+
+                    min_gallop = min_gallop.saturating_sub(1);
+                    if wins_left < MIN_GALLOP && wins_right < MIN_GALLOP {
+                        break;
+                    }
+                }
+                min_gallop += 2;
+            }
+        }
+        self.min_gallop = usize::max(min_gallop, 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sorting::test_helpers::{
+        self, is_stabelly_sorted, random_comparable_list, test_panic_safety, test_unstable_sort,
+    };
+
+    use super::*;
+
+    test_helpers::basic_sorting_tests!(timsort);
+
+    fn count_comparisons(items: &mut [i64]) -> usize {
+        let mut comparisons = 0;
+        timsort_by(items, |a, b| {
+            comparisons += 1;
+            a.cmp(b)
+        });
+        comparisons
+    }
+
+    fn shuffled(len: i64) -> Vec<i64> {
+        (0..len).map(|i| (i * 7919) % len).collect()
+    }
+
+    #[test]
+    fn test_timsort_with_random_items() {
+        test_unstable_sort(&timsort, 10, -1000, 1000);
+        test_unstable_sort(&timsort, 5000, -1000, 1000);
+        test_unstable_sort(&timsort, 5000, -10, 10);
+    }
+
+    #[test]
+    fn test_timsort_is_stable() {
+        for len in [2, 63, 64, 65, 1000, 5000] {
+            let mut data = random_comparable_list(len, 0, 20);
+            timsort(&mut data);
+            assert!(is_stabelly_sorted(&data));
+        }
+    }
+
+    #[test]
+    fn test_timsort_sorts_completely() {
+        let mut items = shuffled(10007);
+        timsort(&mut items);
+        assert_eq!(items, (0..10007).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_timsort_by_key() {
+        let mut items: Vec<(i32, usize)> = (0..500).map(|i| ((i * 37) % 11, i as usize)).collect();
+        timsort_by_key(&mut items, |item| item.0);
+        assert!(
+            items
+                .windows(2)
+                .all(|pair| pair[0].0 < pair[1].0
+                    || (pair[0].0 == pair[1].0 && pair[0].1 < pair[1].1))
+        );
+    }
+
+    #[test]
+    fn test_timsort_is_linear_on_sorted_input() {
+        let mut items: Vec<i64> = (0..10000).collect();
+        assert_eq!(count_comparisons(&mut items), 9999);
+        assert_eq!(items, (0..10000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_timsort_is_linear_on_reversed_input() {
+        let mut items: Vec<i64> = (0..10000).rev().collect();
+        assert_eq!(count_comparisons(&mut items), 9999);
+        assert_eq!(items, (0..10000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_timsort_merges_sorted_chunks_in_linear_time() {
+        let len = 10000;
+        let chunks = 8;
+        let mut items = shuffled(len);
+        for chunk in items.chunks_mut(len as usize / chunks) {
+            chunk.sort();
+        }
+        // finding the runs takes n - 1 comparisons, merging them about n per level of the merge tree
+        let comparisons = count_comparisons(&mut items);
+        assert!(comparisons < len as usize * 5);
+        assert_eq!(items, (0..len).collect::<Vec<_>>());
+
+        let mut items = shuffled(len);
+        assert!(count_comparisons(&mut items) > 2 * comparisons);
+    }
+
+    #[test]
+    fn test_timsort_gallops_over_long_streaks() {
+        // one long run with a gap in the middle, followed by a run that fills the gap
+        let mut items: Vec<i64> = (0..1000).chain(2000..3000).chain(1000..2000).collect();
+        let comparisons = count_comparisons(&mut items);
+        // without galloping the merge alone would need about 1000 comparisons
+        assert!(comparisons < items.len() + 100);
+        assert_eq!(items, (0..3000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_timsort_gallops_in_both_directions() {
+        // interleaved blocks of growing length force merge_lo and merge_hi in and out of galloping
+        let mut left = Vec::new();
+        let mut right = Vec::new();
+        let mut next = 0;
+        for block in 1..40 {
+            for _ in 0..block {
+                left.push(next);
+                next += 1;
+            }
+            for _ in 0..(40 - block) {
+                right.push(next);
+                next += 1;
+            }
+        }
+        let expected: Vec<i64> = (0..next).collect();
+
+        let mut items: Vec<i64> = left.iter().chain(right.iter()).cloned().collect();
+        count_comparisons(&mut items);
+        assert_eq!(items, expected);
+
+        let mut items: Vec<i64> = right.iter().chain(left.iter()).cloned().collect();
+        count_comparisons(&mut items);
+        assert_eq!(items, expected);
+    }
+
+    #[test]
+    fn test_gallop_finds_partition_points() {
+        let items = [1, 2, 2, 2, 3, 5, 8, 8, 13];
+        for hint in 0..items.len() {
+            for key in 0..15 {
+                let is_less = &mut |a: &i32, b: &i32| a < b;
+                assert_eq!(
+                    gallop_left(&key, &items, hint, is_less),
+                    items.partition_point(|item| *item < key)
+                );
+                assert_eq!(
+                    gallop_right(&key, &items, hint, is_less),
+                    items.partition_point(|item| *item <= key)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_timsort_is_panic_safe() {
+        test_panic_safety(timsort, 40);
+        test_panic_safety(timsort, 200);
+    }
+
+    #[test]
+    fn test_timsort_conformance() {
+        test_helpers::test_stable_sort_conformance(timsort);
+        test_helpers::test_sort_conformance(timsort::<String>);
+    }
+}
@@ -0,0 +1,271 @@
+use std::cell::RefCell;
+use std::fmt::{self, Debug, Display};
+use std::io::{self, Write};
+
+use super::bubblesort::bubblesort_impl;
+use super::instrumented::SortProbe;
+use super::pivot::MedianOfThree;
+use super::quicksort::quicksort_impl;
+
+/// A single step of a traced sort. Indexes always refer to the whole slice that is sorted.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum SortEvent {
+    /// The elements at the two indexes were compared, asking if the first one is less than the second one
+    Compare(usize, usize),
+    /// The elements at the two indexes were swapped
+    Swap(usize, usize),
+    /// The range `lo..hi` is about to be partitioned
+    PartitionRange(usize, usize),
+    /// The element at the index was chosen as pivot
+    PivotChosen(usize),
+    /// The partition finished and the elements equal to the pivot are in their final place `lo..hi`
+    PivotsPlaced(usize, usize),
+}
+
+impl Display for SortEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SortEvent::Compare(a, b) => write!(f, "compare {} and {}", a, b),
+            SortEvent::Swap(a, b) => write!(f, "swap {} and {}", a, b),
+            SortEvent::PartitionRange(lo, hi) => write!(f, "partition {}..{}", lo, hi),
+            SortEvent::PivotChosen(pivot) => write!(f, "pivot {}", pivot),
+            SortEvent::PivotsPlaced(lo, hi) => write!(f, "pivots at {}..{}", lo, hi),
+        }
+    }
+}
+
+/// Receives the events of a traced sort in the order they happen
+pub trait SortObserver {
+    fn on_event(&mut self, event: SortEvent);
+}
+
+impl<F: FnMut(SortEvent)> SortObserver for F {
+    fn on_event(&mut self, event: SortEvent) {
+        self(event)
+    }
+}
+
+/// Writes a line with the event and the state of the slice after it for every event.
+/// Keeps its own copy of the slice and replays the swaps on it.
+pub struct TextRenderer<T, W: Write> {
+    state: Vec<T>,
+    out: W,
+    error: Option<io::Error>,
+}
+
+impl<T: Clone + Debug, W: Write> TextRenderer<T, W> {
+    /// Creates a renderer for sorting `elements`, which have to be in the state before the sort
+    pub fn new(elements: &[T], out: W) -> Self {
+        TextRenderer {
+            state: elements.to_vec(),
+            out,
+            error: None,
+        }
+    }
+
+    /// Returns the writer, or the first error that happened while writing
+    pub fn finish(self) -> io::Result<W> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.out),
+        }
+    }
+}
+
+impl<T: Clone + Debug, W: Write> SortObserver for TextRenderer<T, W> {
+    fn on_event(&mut self, event: SortEvent) {
+        if let SortEvent::Swap(a, b) = event {
+            self.state.swap(a, b);
+        }
+        if self.error.is_none() {
+            if let Err(error) = writeln!(self.out, "{}: {:?}", event, self.state) {
+                self.error = Some(error);
+            }
+        }
+    }
+}
+
+/// Sorts the elements like `bubblesort` and reports every step to `observer`
+pub fn bubblesort_traced<T: PartialOrd, O: SortObserver>(elements: &mut [T], observer: &mut O) {
+    let tracer = Tracer::new(elements, observer);
+    bubblesort_impl(
+        elements,
+        &mut |a: &T, b: &T| tracer.is_less(a, b),
+        &mut &tracer,
+    );
+}
+
+/// Sorts the elements like `quicksort` and reports every step to `observer`
+pub fn quicksort_traced<T: PartialOrd, O: SortObserver>(elements: &mut [T], observer: &mut O) {
+    let tracer = Tracer::new(elements, observer);
+    quicksort_impl(
+        elements,
+        &mut |a: &T, b: &T| tracer.is_less(a, b),
+        &mut MedianOfThree,
+        &mut &tracer,
+    );
+}
+
+/// Reports comparisons and the steps of a sort to an observer. Turns the references and slices the sort
+/// works on back into indexes of the sorted slice, using the address of its first element.
+struct Tracer<'a, O> {
+    start: usize,
+    element_size: usize,
+    observer: RefCell<&'a mut O>,
+}
+
+impl<'a, O: SortObserver> Tracer<'a, O> {
+    fn new<T>(elements: &[T], observer: &'a mut O) -> Self {
+        Tracer {
+            start: elements.as_ptr() as usize,
+            element_size: std::mem::size_of::<T>(),
+            observer: RefCell::new(observer),
+        }
+    }
+
+    fn emit(&self, event: SortEvent) {
+        self.observer.borrow_mut().on_event(event);
+    }
+
+    fn is_less<T: PartialOrd>(&self, a: &T, b: &T) -> bool {
+        self.emit(SortEvent::Compare(self.index_of(a), self.index_of(b)));
+        a < b
+    }
+
+    fn index_of<T>(&self, element: *const T) -> usize {
+        if self.element_size == 0 {
+            // all zero sized elements share one address
+            return 0;
+        }
+        (element as usize - self.start) / self.element_size
+    }
+}
+
+impl<O: SortObserver> SortProbe for &Tracer<'_, O> {
+    fn swapped<T>(&mut self, elements: &[T], a: usize, b: usize) {
+        let start = self.index_of(elements.as_ptr());
+        self.emit(SortEvent::Swap(start + a, start + b));
+    }
+
+    fn partitioning<T>(&mut self, elements: &[T]) {
+        let start = self.index_of(elements.as_ptr());
+        self.emit(SortEvent::PartitionRange(start, start + elements.len()));
+    }
+
+    fn pivot_chosen<T>(&mut self, elements: &[T], pivot: usize) {
+        let start = self.index_of(elements.as_ptr());
+        self.emit(SortEvent::PivotChosen(start + pivot));
+    }
+
+    fn partitioned<T>(&mut self, elements: &[T], lo: usize, hi: usize) {
+        let start = self.index_of(elements.as_ptr());
+        self.emit(SortEvent::PivotsPlaced(start + lo, start + hi));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::seq::SliceRandom;
+
+    use super::*;
+
+    #[test]
+    fn test_bubblesort_events() {
+        let mut items = [3, 1, 2];
+        let mut events = Vec::new();
+        bubblesort_traced(&mut items, &mut |event| events.push(event));
+        assert_eq!(items, [1, 2, 3]);
+        assert_eq!(
+            events,
+            [
+                SortEvent::Compare(1, 0),
+                SortEvent::Swap(0, 1),
+                SortEvent::Compare(2, 1),
+                SortEvent::Swap(1, 2),
+                SortEvent::Compare(1, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_quicksort_events_replay_the_sort() {
+        let mut items: Vec<u32> = (0..200).map(|i| i % 50).collect();
+        items.shuffle(&mut rand::thread_rng());
+        let mut replayed = items.clone();
+        let mut events = Vec::new();
+        quicksort_traced(&mut items, &mut |event| events.push(event));
+
+        let mut range = (0, items.len());
+        let mut pivot = None;
+        for event in events {
+            match event {
+                SortEvent::Compare(a, b) => assert!(a < items.len() && b < items.len()),
+                SortEvent::Swap(a, b) => replayed.swap(a, b),
+                SortEvent::PartitionRange(lo, hi) => {
+                    assert!(lo < hi && hi <= items.len());
+                    range = (lo, hi);
+                }
+                SortEvent::PivotChosen(index) => {
+                    assert!(range.0 <= index && index < range.1);
+                    pivot = Some(replayed[index]);
+                }
+                SortEvent::PivotsPlaced(lo, hi) => {
+                    assert!(range.0 <= lo && lo < hi && hi <= range.1);
+                    assert!(replayed[lo..hi].iter().all(|item| Some(*item) == pivot));
+                }
+            }
+        }
+        assert_eq!(replayed, items);
+        assert!(items.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn test_quicksort_starts_with_the_whole_range() {
+        let mut events = Vec::new();
+        quicksort_traced(&mut [2, 1, 3], &mut |event| events.push(event));
+        assert_eq!(events[0], SortEvent::PartitionRange(0, 3));
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, SortEvent::PivotChosen(_))));
+    }
+
+    #[test]
+    fn test_quicksort_never_swaps_an_element_with_itself() {
+        let mut shuffled: Vec<u32> = (0..200).map(|i| i % 20).collect();
+        shuffled.shuffle(&mut rand::thread_rng());
+        let inputs: [Vec<u32>; 4] = [
+            vec![3; 64],
+            (0..64).map(|i| i / 2).collect(),
+            vec![2, 2],
+            shuffled,
+        ];
+        for mut items in inputs {
+            let mut events = Vec::new();
+            quicksort_traced(&mut items, &mut |event| events.push(event));
+            assert!(
+                !events
+                    .iter()
+                    .any(|event| matches!(event, SortEvent::Swap(a, b) if a == b)),
+                "{:?}",
+                events
+            );
+        }
+    }
+
+    #[test]
+    fn test_text_renderer() {
+        let mut items = [2, 1];
+        let mut renderer = TextRenderer::new(&items, Vec::new());
+        bubblesort_traced(&mut items, &mut renderer);
+        let output = String::from_utf8(renderer.finish().unwrap()).unwrap();
+        assert_eq!(output, "compare 1 and 0: [2, 1]\nswap 0 and 1: [1, 2]\n");
+    }
+
+    #[test]
+    fn test_zero_sized_elements() {
+        let mut items = [(), (), ()];
+        let mut count = 0;
+        quicksort_traced(&mut items, &mut |_| count += 1);
+        assert!(count > 0);
+    }
+}
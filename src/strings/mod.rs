@@ -0,0 +1,5 @@
+mod multikey_quicksort;
+pub use multikey_quicksort::multikey_quicksort;
+
+mod suffix_array;
+pub use suffix_array::{lcp_array, suffix_array};
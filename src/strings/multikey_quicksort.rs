@@ -0,0 +1,127 @@
+use std::cmp::Ordering;
+
+use crate::sorting::{insertionsort_by, sort_network};
+
+/// Slices at or below this length are finished with insertion sort
+const INSERTION_SORT_THRESHOLD: usize = 16;
+
+/// Sorts byte strings like `&str`, `String` or `Vec<u8>` lexicographically with Bentley and Sedgewick's
+/// multikey quicksort. Every partition only looks at the byte at the current depth and splits into the
+/// strings with a smaller, an equal and a greater byte. Only the equal part moves on to the next byte, so
+/// common prefixes are looked at once instead of in every comparison as with `quicksort`.
+/// Equal elements may be reordered.
+pub fn multikey_quicksort<T: AsRef<[u8]>>(elements: &mut [T]) {
+    multikey_quicksort_impl(elements, 0);
+}
+
+/// Returns the byte of the element at `depth`, or `None` if it ended before, which orders it before
+/// every string that continues
+fn byte_at<T: AsRef<[u8]>>(element: &T, depth: usize) -> Option<u8> {
+    element.as_ref().get(depth).copied()
+}
+
+fn multikey_quicksort_impl<T: AsRef<[u8]>>(mut elements: &mut [T], mut depth: usize) {
+    loop {
+        let len = elements.len();
+        if len <= INSERTION_SORT_THRESHOLD {
+            // all elements share the first depth bytes
+            insertionsort_by(elements, |a, b| {
+                a.as_ref()[depth..].cmp(&b.as_ref()[depth..])
+            });
+            return;
+        }
+
+        let mut samples = [
+            byte_at(&elements[0], depth),
+            byte_at(&elements[len / 2], depth),
+            byte_at(&elements[len - 1], depth),
+        ];
+        sort_network(&mut samples);
+        let pivot = samples[1];
+
+        // elements[..lt] have a smaller byte, elements[lt..i] the pivot byte, elements[gt..] a greater byte
+        // and elements[i..gt] are not looked at yet
+        let mut lt = 0;
+        let mut i = 0;
+        let mut gt = len;
+        while i < gt {
+            match byte_at(&elements[i], depth).cmp(&pivot) {
+                Ordering::Less => {
+                    elements.swap(lt, i);
+                    lt += 1;
+                    i += 1;
+                }
+                Ordering::Equal => i += 1,
+                Ordering::Greater => {
+                    gt -= 1;
+                    elements.swap(i, gt);
+                }
+            }
+        }
+
+        let (less, rest) = elements.split_at_mut(lt);
+        let (equal, greater) = rest.split_at_mut(gt - lt);
+        multikey_quicksort_impl(less, depth);
+        multikey_quicksort_impl(greater, depth);
+        if pivot.is_none() {
+            // the strings in the equal part all ended, so they are equal
+            return;
+        }
+        // going on with the next byte without recursing keeps long common prefixes from overflowing the
+        // stack
+        elements = equal;
+        depth += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use crate::sorting::test_helpers;
+
+    use super::*;
+
+    #[test]
+    fn test_multikey_quicksort_strings() {
+        let mut generator = rand::thread_rng();
+        let mut words: Vec<String> = (0..3000)
+            .map(|_| {
+                let len = generator.gen_range(0..8);
+                (0..len)
+                    .map(|_| generator.gen_range(b'a'..=b'e') as char)
+                    .collect()
+            })
+            .collect();
+        let mut expected = words.clone();
+        expected.sort();
+        multikey_quicksort(&mut words);
+        assert_eq!(words, expected);
+    }
+
+    #[test]
+    fn test_multikey_quicksort_bytes_and_prefixes() {
+        let mut items: Vec<&[u8]> = vec![b"ab", b"", b"a", b"\xff", b"abc", b"ab\x00", b"b", b"a"];
+        items.extend(std::iter::repeat_n(&b"abd"[..], 40));
+        items.extend(std::iter::repeat_n(&b""[..], 20));
+        let mut expected = items.clone();
+        expected.sort();
+        multikey_quicksort(&mut items);
+        assert_eq!(items, expected);
+    }
+
+    #[test]
+    fn test_multikey_quicksort_long_common_prefixes() {
+        let prefix = "x".repeat(100_000);
+        let mut items: Vec<String> = (0..100).rev().map(|i| format!("{}{}", prefix, i)).collect();
+        let mut expected = items.clone();
+        expected.sort();
+        multikey_quicksort(&mut items);
+        assert_eq!(items, expected);
+    }
+
+    #[test]
+    fn test_multikey_quicksort_conformance() {
+        test_helpers::test_sort_conformance(multikey_quicksort::<String>);
+    }
+}
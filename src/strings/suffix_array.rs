@@ -0,0 +1,178 @@
+use crate::sorting::{radix_sort_by_key, RadixKey};
+
+/// Returns the suffix array of the text: the start indexes of all suffixes in lexicographic order.
+/// A suffix that is a prefix of another one comes first. Built by prefix doubling: after sorting the
+/// suffixes by their first `k` elements, the ranks of two halves order them by their first `2k`
+/// elements, which `radix_sort_by_key` sorts in linear time. Takes O(n log n) time.
+pub fn suffix_array<T: RadixKey>(text: &[T]) -> Vec<usize> {
+    let len = text.len();
+    let mut suffixes: Vec<usize> = (0..len).collect();
+    radix_sort_by_key(&mut suffixes, |&suffix| text[suffix]);
+
+    // the rank of every suffix by its first k elements. Ranks start at 1, so 0 can stand for the empty
+    // suffix past the end of the text.
+    let mut rank = vec![0; len];
+    let mut next_rank = vec![0; len];
+    assign_ranks(&suffixes, &mut rank, |suffix| text[suffix].to_radix_key());
+
+    let mut k = 1;
+    // once all ranks are distinct the suffixes are sorted
+    while k < len && rank[suffixes[len - 1]] < len {
+        let second_half = |suffix: usize| rank.get(suffix + k).copied().unwrap_or(0);
+        // the radix sort is stable, so sorting by the first half afterwards keeps ties ordered by the
+        // second half
+        radix_sort_by_key(&mut suffixes, |&suffix| second_half(suffix));
+        radix_sort_by_key(&mut suffixes, |&suffix| rank[suffix]);
+        assign_ranks(&suffixes, &mut next_rank, |suffix| {
+            (rank[suffix], second_half(suffix))
+        });
+        std::mem::swap(&mut rank, &mut next_rank);
+        k *= 2;
+    }
+    suffixes
+}
+
+/// Gives the sorted suffixes consecutive ranks starting at 1, suffixes with the same key share one
+fn assign_ranks<K: PartialEq, F: Fn(usize) -> K>(suffixes: &[usize], rank: &mut [usize], key: F) {
+    let mut current = 0;
+    let mut previous = None;
+    for &suffix in suffixes {
+        let key = key(suffix);
+        if previous.as_ref() != Some(&key) {
+            current += 1;
+        }
+        rank[suffix] = current;
+        previous = Some(key);
+    }
+}
+
+/// Returns the longest common prefix array of the text with Kasai's algorithm. Entry `i` is the length of
+/// the longest common prefix of the suffixes at `suffix_array[i - 1]` and `suffix_array[i]`, entry 0
+/// is 0. Takes O(n) time, because moving from one suffix to the next shorter one loses at most one
+/// element of the prefix.
+///
+/// Panics if `suffix_array` is not as long as the text.
+pub fn lcp_array<T: PartialEq>(text: &[T], suffix_array: &[usize]) -> Vec<usize> {
+    let len = text.len();
+    assert_eq!(
+        suffix_array.len(),
+        len,
+        "the suffix array must have one entry per element of the text"
+    );
+    let mut rank = vec![0; len];
+    for (position, &suffix) in suffix_array.iter().enumerate() {
+        rank[suffix] = position;
+    }
+
+    let mut lcp = vec![0; len];
+    let mut common = 0;
+    for suffix in 0..len {
+        if rank[suffix] == 0 {
+            common = 0;
+            continue;
+        }
+        let previous = suffix_array[rank[suffix] - 1];
+        while suffix + common < len
+            && previous + common < len
+            && text[suffix + common] == text[previous + common]
+        {
+            common += 1;
+        }
+        lcp[rank[suffix]] = common;
+        common = common.saturating_sub(1);
+    }
+    lcp
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+
+    fn naive_suffix_array<T: Ord>(text: &[T]) -> Vec<usize> {
+        let mut suffixes: Vec<usize> = (0..text.len()).collect();
+        suffixes.sort_by(|&a, &b| text[a..].cmp(&text[b..]));
+        suffixes
+    }
+
+    fn naive_lcp_array<T: PartialEq>(text: &[T], suffix_array: &[usize]) -> Vec<usize> {
+        let mut lcp = vec![0; text.len()];
+        for i in 1..text.len() {
+            lcp[i] = text[suffix_array[i - 1]..]
+                .iter()
+                .zip(&text[suffix_array[i]..])
+                .take_while(|(a, b)| a == b)
+                .count();
+        }
+        lcp
+    }
+
+    #[test]
+    fn test_suffix_array_of_banana() {
+        let text = b"banana";
+        let suffixes = suffix_array(text);
+        assert_eq!(suffixes, vec![5, 3, 1, 0, 4, 2]);
+        assert_eq!(lcp_array(text, &suffixes), vec![0, 1, 3, 0, 0, 2]);
+    }
+
+    #[test]
+    fn test_suffix_array_edge_cases() {
+        assert!(suffix_array::<u8>(&[]).is_empty());
+        assert!(lcp_array::<u8>(&[], &[]).is_empty());
+        assert_eq!(suffix_array(b"x"), vec![0]);
+        assert_eq!(suffix_array(&[7u8; 10]), (0..10).rev().collect::<Vec<_>>());
+        assert_eq!(
+            lcp_array(&[7u8; 10], &suffix_array(&[7u8; 10])),
+            (0..10).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_suffix_array_against_naive() {
+        let mut generator = rand::thread_rng();
+        for len in [2, 10, 100, 1000] {
+            for alphabet in [2u8, 4, 255] {
+                let text: Vec<u8> = (0..len).map(|_| generator.gen_range(0..alphabet)).collect();
+                let suffixes = suffix_array(&text);
+                assert_eq!(suffixes, naive_suffix_array(&text));
+                assert_eq!(
+                    lcp_array(&text, &suffixes),
+                    naive_lcp_array(&text, &suffixes)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_suffix_array_of_integers() {
+        let text = [3i32, -1, 3, -1, 3, i32::MIN, i32::MAX];
+        let suffixes = suffix_array(&text);
+        assert_eq!(suffixes, naive_suffix_array(&text));
+        assert_eq!(
+            lcp_array(&text, &suffixes),
+            naive_lcp_array(&text, &suffixes)
+        );
+    }
+
+    #[test]
+    fn test_longest_repeated_substring() {
+        let text = "it was the best of times, it was the worst of times".as_bytes();
+        let suffixes = suffix_array(text);
+        let lcp = lcp_array(text, &suffixes);
+        let (position, length) = lcp
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, length)| **length)
+            .unwrap();
+        let start = suffixes[position];
+        // "it was the " and "st of times" both occur twice, the latter comes later in the suffix array
+        assert_eq!(&text[start..start + length], b"st of times");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_lcp_array_rejects_short_suffix_arrays() {
+        lcp_array(b"abc", &[0, 1]);
+    }
+}